@@ -0,0 +1,79 @@
+//! Procedural macros for [medic](https://crates.io/crates/medic)
+//!
+//! Don't use this crate directly, instead enable the `macros` feature of
+//! `medic` and use the re-exports from there.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse_macro_input;
+use syn::spanned::Spanned;
+use syn::ItemFn;
+use syn::LitStr;
+
+/// Register a function as a check
+///
+/// See `medic::check` for documentation.
+#[proc_macro_attribute]
+pub fn check(args: TokenStream, item: TokenStream) -> TokenStream {
+    let func = parse_macro_input!(item as ItemFn);
+    let mut name: Option<LitStr> = None;
+    let mut hint: Option<LitStr> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("hint") {
+            hint = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported property, expected `name` or `hint`"))
+        }
+    });
+    parse_macro_input!(args with parser);
+
+    match expand(&func, name, hint) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+/// Generate the function along with the registration of it
+fn expand(
+    func: &ItemFn,
+    name: Option<LitStr>,
+    hint: Option<LitStr>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &func.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new(
+            asyncness.span(),
+            "checks registered with #[check] can't be async",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            sig.generics.span(),
+            "checks registered with #[check] can't be generic",
+        ));
+    }
+    if !sig.inputs.is_empty() {
+        return Err(syn::Error::new(
+            sig.inputs.span(),
+            "checks registered with #[check] can't take any parameters",
+        ));
+    }
+
+    let ident = &sig.ident;
+    let name =
+        name.unwrap_or_else(|| LitStr::new(&ident.to_string().replace('_', "-"), ident.span()));
+    let hint = hint.map(|hint| quote! { .with_hint(#hint) });
+    Ok(quote! {
+        #func
+
+        ::medic::__private::inventory::submit! {
+            ::medic::__private::RegisteredCheck::new(
+                ::medic::Check::new_const(#name, #ident) #hint
+            )
+        }
+    })
+}
@@ -0,0 +1,242 @@
+//! Support for asynchronous checks
+
+use crate::render_table;
+use crate::render_with;
+use crate::worst_result;
+use crate::CheckOutcome;
+use crate::CheckResult;
+use crate::MedicError;
+use crate::MedicOptions;
+use std::future::Future;
+use std::io::Write;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+/// Future returned by the function of an [`AsyncCheck`]
+pub type CheckFuture = Pin<
+    Box<
+        dyn Future<Output = Result<(CheckResult, String), Box<dyn std::error::Error + Send + Sync>>>
+            + Send,
+    >,
+>;
+
+/// An asynchronous check with a name
+///
+/// This is the counterpart of [`Check`](crate::Check) for checks that need to
+/// do asynchronous I/O (e.g. network probes).
+pub struct AsyncCheck {
+    name: &'static str,
+    func: Box<dyn Fn() -> CheckFuture + Send + Sync>,
+    hint: Option<&'static str>,
+}
+
+impl AsyncCheck {
+    /// Create a new asynchronous check
+    ///
+    /// ```
+    /// use medic::AsyncCheck;
+    /// use medic::CheckResult;
+    ///
+    /// let check = AsyncCheck::new("server", || async {
+    ///     // Connect to the server here
+    ///     Ok((CheckResult::Ok, "Server is up".to_string()))
+    /// });
+    /// ```
+    ///
+    /// * `name`: Name of check (for display)
+    /// * `func`: Function returning a future that performs the check
+    pub fn new<F, Fut>(name: &'static str, func: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(CheckResult, String), Box<dyn std::error::Error + Send + Sync>>>
+            + Send
+            + 'static,
+    {
+        Self {
+            name,
+            func: Box::new(move || Box::pin(func())),
+            hint: None,
+        }
+    }
+
+    /// Attach a suggested fix, shown when the check finds a problem
+    ///
+    /// See [`Check::with_hint`](crate::Check::with_hint).
+    pub const fn with_hint(mut self, hint: &'static str) -> Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    /// Run the check, converting errors to [`CheckResult::Fatal`]
+    async fn run(&self) -> CheckOutcome {
+        let mut outcome = match (self.func)().await {
+            Ok((result, message)) => CheckOutcome {
+                name: self.name,
+                result,
+                message,
+                errored: false,
+                duration: None,
+                hint: None,
+                details: vec![],
+                extended: None,
+            },
+            Err(err) => CheckOutcome {
+                name: self.name,
+                result: CheckResult::Fatal,
+                message: format!("{err}"),
+                errored: true,
+                duration: None,
+                hint: None,
+                details: vec![],
+                extended: None,
+            },
+        };
+        if outcome.result >= CheckResult::Warning {
+            outcome.hint = self.hint.map(str::to_owned);
+        }
+        outcome
+    }
+}
+
+impl std::fmt::Debug for AsyncCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncCheck")
+            .field("name", &self.name)
+            .field("hint", &self.hint)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Run asynchronous checks (one at a time), returning the outcomes
+///
+/// This doesn't depend on any particular async runtime.
+pub async fn run_checks_async<'iter>(
+    checks: impl Iterator<Item = &'iter AsyncCheck>,
+) -> Vec<CheckOutcome> {
+    let mut outcomes = vec![];
+    for check in checks {
+        outcomes.push(check.run().await);
+    }
+    outcomes
+}
+
+/// Run asynchronous checks concurrently, returning the outcomes in the
+/// original order
+///
+/// All checks are polled from the calling task (nothing is spawned), so this
+/// doesn't depend on any particular async runtime either. While a check waits
+/// for I/O the other checks make progress.
+pub async fn run_checks_async_concurrent<'iter>(
+    checks: impl Iterator<Item = &'iter AsyncCheck>,
+) -> Vec<CheckOutcome> {
+    let pending: Vec<_> = checks
+        .map(|check| {
+            let future: OutcomeFuture<'iter> = Box::pin(check.run());
+            Some(future)
+        })
+        .collect();
+    let done = pending.iter().map(|_| None).collect();
+    JoinAll { pending, done }.await
+}
+
+/// Run asynchronous checks with the given options, returning the outcomes
+///
+/// With [`MedicOptions::parallel`] the checks are run concurrently (see
+/// [`run_checks_async_concurrent`]), and redaction (see
+/// [`MedicOptions::redact`]) is applied to the outcomes. Timing and timeouts
+/// are not supported for asynchronous checks, use the facilities of your
+/// async runtime for those.
+pub async fn run_checks_async_with<'iter>(
+    checks: impl Iterator<Item = &'iter AsyncCheck>,
+    options: &MedicOptions,
+) -> Vec<CheckOutcome> {
+    let mut outcomes = if options.parallel {
+        run_checks_async_concurrent(checks).await
+    } else {
+        run_checks_async(checks).await
+    };
+    if let Some(rules) = &options.redaction {
+        for outcome in &mut outcomes {
+            rules.apply_to(outcome);
+        }
+    }
+    outcomes
+}
+
+/// Future for the outcome of a single check
+type OutcomeFuture<'check> = Pin<Box<dyn Future<Output = CheckOutcome> + Send + 'check>>;
+
+/// Future that polls all the checks until they are done
+struct JoinAll<'check> {
+    /// Futures that are still running (`None` once done)
+    pending: Vec<Option<OutcomeFuture<'check>>>,
+    /// Outcomes of the futures that are done
+    done: Vec<Option<CheckOutcome>>,
+}
+
+impl Future for JoinAll<'_> {
+    type Output = Vec<CheckOutcome>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        for (slot, outcome) in this.pending.iter_mut().zip(&mut this.done) {
+            if let Some(future) = slot {
+                if let Poll::Ready(ready) = future.as_mut().poll(cx) {
+                    *outcome = Some(ready);
+                    *slot = None;
+                }
+            }
+        }
+        if this.pending.iter().all(Option::is_none) {
+            Poll::Ready(this.done.iter_mut().filter_map(Option::take).collect())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Perform environment sanity check using asynchronous checks
+///
+/// The output is the same table as for [`medic`](crate::medic).
+///
+/// Returns the worst level found (which can be passed to
+/// [`summary`](crate::summary))
+pub async fn medic_async<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter AsyncCheck>,
+) -> Result<CheckResult, MedicError> {
+    let outcomes = run_checks_async(checks).await;
+    render_table(output, &outcomes)?;
+    Ok(worst_result(&outcomes))
+}
+
+/// Perform environment sanity check using asynchronous checks, running them
+/// concurrently
+///
+/// See [`run_checks_async_concurrent`] and [`medic_async`].
+pub async fn medic_async_concurrent<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter AsyncCheck>,
+) -> Result<CheckResult, MedicError> {
+    let outcomes = run_checks_async_concurrent(checks).await;
+    render_table(output, &outcomes)?;
+    Ok(worst_result(&outcomes))
+}
+
+/// Perform environment sanity check using asynchronous checks, with options
+/// controlling how checks are run and shown
+///
+/// See [`run_checks_async_with`] and
+/// [`medic_with_options`](crate::medic_with_options).
+pub async fn medic_async_with_options<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter AsyncCheck>,
+    options: &MedicOptions,
+) -> Result<CheckResult, MedicError> {
+    let outcomes = run_checks_async_with(checks, options).await;
+    if let Some(shown) = options.visible(&outcomes) {
+        render_with(output, &shown, &options.table_renderer())?;
+    }
+    Ok(worst_result(&outcomes))
+}
@@ -3,16 +3,70 @@
 use crate::Check;
 use crate::CheckResult;
 
+mod build_info;
+mod daemon;
+mod env;
+#[cfg(feature = "fonts")]
+mod fonts;
+mod fs;
+mod install_source;
+mod line_endings;
+mod locale;
+mod path;
+mod translocation;
+#[cfg(any(target_os = "linux", windows))]
+mod tunables;
+
+#[doc(hidden)]
+pub use build_info::BuildInfo;
+pub use daemon::daemon_health;
+pub use daemon::DaemonAddr;
+pub use daemon::HealthProbe;
+pub use env::check_env_vars;
+pub use env::check_env_vars_redacting;
+pub use env::env_var_check;
+#[cfg(test)]
+pub(crate) use env::evaluate_env_var;
+#[cfg(test)]
+pub(crate) use env::report_env_vars;
+pub use env::EnvExpectation;
+pub use env::DEFAULT_REDACT_PATTERNS;
+#[cfg(feature = "fonts")]
+pub use fonts::font_available;
+pub use fs::exists_check;
+#[cfg(feature = "free-space")]
+pub use fs::free_space_check;
+pub use fs::readable_tree;
+pub use fs::writable_check;
+#[cfg(test)]
+pub(crate) use install_source::classify_install_path;
+pub use install_source::CHECK_INSTALL_SOURCE;
+pub use line_endings::line_endings;
+pub use line_endings::LineEnding;
+#[cfg(all(test, unix))]
+pub(crate) use locale::is_utf8;
+#[cfg(all(test, unix))]
+pub(crate) use locale::lossy_vars;
+#[cfg(all(test, unix))]
+pub(crate) use locale::report_locale;
+pub use locale::CHECK_LOCALE;
+pub use path::check_in_path;
+pub use path::executable_check;
+#[cfg(test)]
+pub(crate) use translocation::is_translocated;
+pub use translocation::CHECK_TRANSLOCATION;
+#[cfg(windows)]
+pub use tunables::registry_value;
+#[cfg(target_os = "linux")]
+pub use tunables::sysctl;
+
 /// Provide info on the rust version used to compile the code
-pub const CHECK_RUSTC_VERSION: Check = Check {
-    name: "rustc-version",
-    func: || {
-        Ok((
-            CheckResult::Ok,
-            format!("{}", rustc_version_runtime::version()),
-        ))
-    },
-};
+pub const CHECK_RUSTC_VERSION: Check = Check::new_const("rustc-version", || {
+    Ok((
+        CheckResult::Ok,
+        format!("{}", rustc_version_runtime::version()),
+    ))
+});
 
 /// Create a version check (for information only) for the crate the macro is
 /// called from.
@@ -20,7 +74,7 @@ pub const CHECK_RUSTC_VERSION: Check = Check {
 #[macro_export]
 macro_rules! crate_version_check {
     () => {
-        Check::new("version", || {
+        Check::new_const("version", || {
             Ok((CheckResult::Ok, env!("CARGO_PKG_VERSION").to_string()))
         })
     };
@@ -29,19 +83,116 @@ macro_rules! crate_version_check {
 #[doc(inline)]
 pub use crate_version_check;
 
+/// Create a check (for information only) reporting how the crate the macro is
+/// called from was built.
+///
+/// This shows the profile (debug or release, based on `debug_assertions`),
+/// the target triple, the enabled cargo features, the git commit and the
+/// build timestamp. Apart from the profile, these are taken from environment
+/// variables at compile time, following the conventions of
+/// [vergen](https://crates.io/crates/vergen) (set them from your build
+/// script):
+///
+/// * Target: `VERGEN_CARGO_TARGET_TRIPLE` or `TARGET` (otherwise the
+///   architecture and OS are shown)
+/// * Features: `VERGEN_CARGO_FEATURES`
+/// * Commit: `VERGEN_GIT_SHA` or `GIT_HASH`
+/// * Timestamp: `VERGEN_BUILD_TIMESTAMP` or `SOURCE_DATE_EPOCH`
+///
+/// Anything that isn't set is left out. Alternatively, list the features of
+/// your crate to report, and the enabled ones are determined with `cfg!`:
+///
+/// ```
+/// use medic::checks::build_info_check;
+///
+/// let check = build_info_check!();
+/// let check = build_info_check!(features = ["fonts", "serde"]);
+/// ```
+///
+/// The details are shown below the message (see
+/// [`Check::new_detailed`](crate::Check::new_detailed)).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! build_info_check {
+    () => {
+        $crate::build_info_check!(@build None)
+    };
+    (features = [$($feature:literal),* $(,)?]) => {
+        $crate::build_info_check!(@build Some(&[$(($feature, cfg!(feature = $feature))),*]))
+    };
+    (@build $features:expr) => {
+        $crate::Check::new_detailed("build", || {
+            Ok($crate::checks::BuildInfo {
+                debug: cfg!(debug_assertions),
+                target: option_env!("VERGEN_CARGO_TARGET_TRIPLE").or(option_env!("TARGET")),
+                features: $features,
+                env_features: option_env!("VERGEN_CARGO_FEATURES"),
+                commit: option_env!("VERGEN_GIT_SHA").or(option_env!("GIT_HASH")),
+                timestamp: option_env!("VERGEN_BUILD_TIMESTAMP")
+                    .or(option_env!("SOURCE_DATE_EPOCH")),
+            }
+            .report())
+        })
+    };
+}
+
+#[doc(inline)]
+pub use build_info_check;
+
+/// Create a check that compares a value obtained at runtime with an expected
+/// value (such as an ABI or schema version).
+///
+/// * `name`: Name of check (for display)
+/// * `getter_expr`: Expression evaluated when the check runs
+/// * `expected`: Expected value (evaluated once, when the check is created)
+///
+/// Any variables used in the expressions are moved into the check. The
+/// expected value is compared by reference, so it need not be [`Copy`] (for
+/// example a [`String`]).
+///
+/// The check results in [`CheckResult::Ok`] when the values are equal and
+/// [`CheckResult::Error`] (showing both values) otherwise. The values need to
+/// implement [`PartialEq`] and [`Display`](std::fmt::Display).
+///
+/// ```
+/// use medic::checks::expect_eq_check;
+///
+/// const SCHEMA_VERSION: u32 = 3;
+/// # fn schema_version_on_disk() -> u32 { 3 }
+/// let check = expect_eq_check!("schema-version", schema_version_on_disk(), SCHEMA_VERSION);
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! expect_eq_check {
+    ($name:expr, $getter:expr, $expected:expr) => {{
+        let expected = $expected;
+        $crate::Check::new($name, move || {
+            let actual = $getter;
+            if actual == expected {
+                Ok(($crate::CheckResult::Ok, format!("{actual}")))
+            } else {
+                Ok((
+                    $crate::CheckResult::Error,
+                    format!("Expected {expected}, found {actual}"),
+                ))
+            }
+        })
+    }};
+}
+
+#[doc(inline)]
+pub use expect_eq_check;
+
 /// Provide info on the running host system and architecture
-pub const CHECK_HOST: Check = Check {
-    name: "host",
-    func: || {
-        let info = os_info::get();
-        Ok((
-            CheckResult::Ok,
-            format!(
-                "os={}, arch={}, info={}",
-                std::env::consts::OS,
-                std::env::consts::ARCH,
-                info
-            ),
-        ))
-    },
-};
+pub const CHECK_HOST: Check = Check::new_const("host", || {
+    let info = os_info::get();
+    Ok((
+        CheckResult::Ok,
+        format!(
+            "os={}, arch={}, info={}",
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            info
+        ),
+    ))
+});
@@ -0,0 +1,71 @@
+//! Report how the program was built
+
+use crate::CheckResult;
+use crate::DetailedResult;
+
+/// Build information, as collected by
+/// [`build_info_check!`](crate::checks::build_info_check)
+///
+/// Not public API, only used by the macro.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct BuildInfo {
+    /// True for a debug build (`debug_assertions` enabled)
+    pub debug: bool,
+    /// Target triple, if known (otherwise arch and OS are shown)
+    pub target: Option<&'static str>,
+    /// Features passed to the macro, and whether they are enabled
+    pub features: Option<&'static [(&'static str, bool)]>,
+    /// Enabled features according to the build environment
+    pub env_features: Option<&'static str>,
+    /// Git commit the program was built from, if known
+    pub commit: Option<&'static str>,
+    /// When the program was built (in whatever format the build environment
+    /// uses), if known
+    pub timestamp: Option<&'static str>,
+}
+
+impl BuildInfo {
+    /// Create the result of the check
+    pub fn report(&self) -> DetailedResult {
+        let profile = if self.debug { "debug" } else { "release" };
+        let target = match self.target {
+            Some(target) => target.to_owned(),
+            None => format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+        };
+        let features = match (self.features, self.env_features) {
+            (Some(features), _) => {
+                let enabled: Vec<_> = features
+                    .iter()
+                    .filter(|(_, enabled)| *enabled)
+                    .map(|(feature, _)| *feature)
+                    .collect();
+                Some(if enabled.is_empty() {
+                    "none".to_owned()
+                } else {
+                    enabled.join(", ")
+                })
+            }
+            (None, features) => features.map(str::to_owned),
+        };
+
+        let mut message = format!("{profile} build for {target}");
+        if let Some(commit) = self.commit {
+            message.push_str(" from ");
+            message.push_str(commit);
+        }
+        let mut result = DetailedResult::new(CheckResult::Ok, message)
+            .detail("profile", profile)
+            .detail("target", target);
+        if let Some(features) = features {
+            result = result.detail("features", features);
+        }
+        if let Some(commit) = self.commit {
+            result = result.detail("commit", commit);
+        }
+        if let Some(timestamp) = self.timestamp {
+            result = result.detail("built", timestamp);
+        }
+        result
+    }
+}
@@ -0,0 +1,160 @@
+//! Check that a local daemon is reachable and healthy
+
+use crate::Check;
+use crate::CheckResult;
+use std::io::Read;
+use std::io::Write;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Timeout for connecting, sending and receiving
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Address of a daemon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaemonAddr {
+    /// TCP address (`host:port`)
+    Tcp(String),
+    /// Unix domain socket
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl std::fmt::Display for DaemonAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => f.write_str(addr),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Health probe to send after connecting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthProbe {
+    /// Data to send to the daemon
+    pub request: Vec<u8>,
+    /// If given, the response must start with this
+    pub expected_response: Option<Vec<u8>>,
+}
+
+/// A connected socket
+trait Stream: Read + Write {}
+
+impl<T: Read + Write> Stream for T {}
+
+/// Connect to a socket, with timeouts set up for reading and writing
+fn connect(addr: &DaemonAddr) -> std::io::Result<Box<dyn Stream>> {
+    match addr {
+        DaemonAddr::Tcp(addr) => {
+            let mut last_err = None;
+            for sock_addr in std::net::ToSocketAddrs::to_socket_addrs(addr.as_str())? {
+                match std::net::TcpStream::connect_timeout(&sock_addr, TIMEOUT) {
+                    Ok(stream) => {
+                        stream.set_read_timeout(Some(TIMEOUT))?;
+                        stream.set_write_timeout(Some(TIMEOUT))?;
+                        return Ok(Box::new(stream));
+                    }
+                    Err(err) => last_err = Some(err),
+                }
+            }
+            Err(last_err.unwrap_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "Address did not resolve to anything",
+                )
+            }))
+        }
+        #[cfg(unix)]
+        DaemonAddr::Unix(path) => {
+            let stream = std::os::unix::net::UnixStream::connect(path)?;
+            stream.set_read_timeout(Some(TIMEOUT))?;
+            stream.set_write_timeout(Some(TIMEOUT))?;
+            Ok(Box::new(stream))
+        }
+    }
+}
+
+/// Send the probe and read the response
+fn send_probe(stream: &mut dyn Stream, probe: &HealthProbe) -> std::io::Result<Vec<u8>> {
+    stream.write_all(&probe.request)?;
+    stream.flush()?;
+    let wanted = probe.expected_response.as_ref().map_or(1, Vec::len);
+    let mut response = vec![];
+    let mut buf = [0; 1024];
+    while response.len() < wanted {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        response.extend_from_slice(&buf[..n]);
+    }
+    Ok(response)
+}
+
+/// Check that a daemon is reachable and healthy
+///
+/// This connects to the daemon, then (if a probe is given) sends the probe
+/// request and waits for a response. The result is:
+///
+/// * [`CheckResult::Error`]: Could not connect.
+/// * [`CheckResult::Warning`]: Connected, but the probe failed, got no
+///   response or got an unexpected response.
+/// * [`CheckResult::Ok`]: Connected (and got the expected response).
+///
+/// The round-trip time is included in the message.
+///
+/// * `addr`: Address of the daemon
+/// * `probe`: Optional health probe to send
+/// * `name`: Name of check (for display)
+pub fn daemon_health(addr: DaemonAddr, probe: Option<HealthProbe>, name: &'static str) -> Check {
+    Check::new(name, move || {
+        let start = Instant::now();
+        let mut stream = match connect(&addr) {
+            Ok(stream) => stream,
+            Err(err) => {
+                return Ok((
+                    CheckResult::Error,
+                    format!("Failed to connect to {addr}: {err}"),
+                ))
+            }
+        };
+        let Some(probe) = &probe else {
+            return Ok((
+                CheckResult::Ok,
+                format!("Connected to {addr} in {:.1?}", start.elapsed()),
+            ));
+        };
+        let response = send_probe(stream.as_mut(), probe);
+        let elapsed = start.elapsed();
+        Ok(match response {
+            Err(err) => (
+                CheckResult::Warning,
+                format!("Connected to {addr}, but health probe failed: {err}"),
+            ),
+            Ok(response) if response.is_empty() => (
+                CheckResult::Warning,
+                format!("Connected to {addr}, but got no response to health probe"),
+            ),
+            Ok(response)
+                if probe
+                    .expected_response
+                    .as_ref()
+                    .is_some_and(|expected| !response.starts_with(expected)) =>
+            {
+                (
+                    CheckResult::Warning,
+                    format!(
+                        "Unexpected health probe response from {addr}: {}",
+                        String::from_utf8_lossy(&response).trim_end()
+                    ),
+                )
+            }
+            Ok(_) => (
+                CheckResult::Ok,
+                format!("{addr} is healthy (round trip {elapsed:.1?})"),
+            ),
+        })
+    })
+}
@@ -0,0 +1,214 @@
+//! Checks for environment variables
+
+use crate::Check;
+use crate::CheckResult;
+
+/// Default patterns for names of environment variables whose values are
+/// redacted by [`check_env_vars`]
+///
+/// Patterns are matched case-insensitively against the whole name, and `*`
+/// matches any sequence of characters.
+pub const DEFAULT_REDACT_PATTERNS: &[&str] = &["*TOKEN*", "*SECRET*", "*PASSWORD*", "*KEY*"];
+
+/// Match a name against a simple glob pattern (only `*` is special)
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_uppercase();
+    let name = name.to_ascii_uppercase();
+    let mut parts = pattern.split('*');
+    // There is always at least one part
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<_> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` in the pattern
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Format `name=value`, redacting the value if the name matches any pattern
+fn display_var(name: &str, value: &str, patterns: &[impl AsRef<str>]) -> String {
+    if patterns
+        .iter()
+        .any(|pattern| glob_match(pattern.as_ref(), name))
+    {
+        format!("{name}=<redacted, len={}>", value.chars().count())
+    } else {
+        format!("{name}={value}")
+    }
+}
+
+/// Report the values of the given environment variables
+///
+/// Values of variables whose names match any of
+/// [`DEFAULT_REDACT_PATTERNS`] are replaced with `<redacted, len=N>`. Use
+/// [`check_env_vars_redacting`] to provide your own patterns.
+///
+/// The result is [`CheckResult::Ok`] if all variables are set, and
+/// [`CheckResult::Info`] if any are not set.
+pub fn check_env_vars(names: &[&str]) -> Check {
+    check_env_vars_redacting(names, DEFAULT_REDACT_PATTERNS)
+}
+
+/// Report the values of the given environment variables, with custom
+/// redaction patterns
+///
+/// See [`check_env_vars`] and [`DEFAULT_REDACT_PATTERNS`]. To extend the
+/// defaults, include them in `redact_patterns`.
+pub fn check_env_vars_redacting(names: &[&str], redact_patterns: &[&str]) -> Check {
+    let names: Vec<String> = names.iter().map(|name| (*name).to_owned()).collect();
+    let patterns: Vec<String> = redact_patterns
+        .iter()
+        .map(|pattern| (*pattern).to_owned())
+        .collect();
+    Check::new("env-vars", move || {
+        Ok(report_env_vars(&names, &patterns, |name| {
+            std::env::var_os(name).map(|value| value.to_string_lossy().into_owned())
+        }))
+    })
+}
+
+/// Report the values of variables (as looked up by `lookup`)
+pub(crate) fn report_env_vars(
+    names: &[impl AsRef<str>],
+    patterns: &[impl AsRef<str>],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> (CheckResult, String) {
+    let mut result = CheckResult::Ok;
+    let lines: Vec<String> = names
+        .iter()
+        .map(AsRef::as_ref)
+        .map(|name| match lookup(name) {
+            None => {
+                result = CheckResult::Info;
+                format!("{name} not set")
+            }
+            Some(value) => display_var(name, &value, patterns),
+        })
+        .collect();
+    (result, lines.join("\n"))
+}
+
+/// What is expected of an environment variable, for [`env_var_check`]
+#[derive(Debug, Clone, Copy)]
+pub enum EnvExpectation {
+    /// The variable should not be set (e.g. a debug override)
+    Unset,
+    /// The variable should be set (to any value)
+    Set,
+    /// The variable should be set to one of the given values
+    OneOf(&'static [&'static str]),
+    /// The variable should be set to a value accepted by the predicate
+    Predicate {
+        /// Returns true if the value is acceptable
+        test: fn(&str) -> bool,
+        /// Description of acceptable values (for the message)
+        description: &'static str,
+    },
+}
+
+/// Check that an environment variable is set or unset as expected
+///
+/// The result is [`CheckResult::Ok`] if the expectation holds, `missing` if
+/// the variable is expected to be set but isn't, and `mismatch` if it is set
+/// but shouldn't be (or to an unexpected value). The message includes the
+/// value (redacted if the name matches any of `redact_patterns`, see
+/// [`check_env_vars_redacting`]), and what was expected if it doesn't hold.
+///
+/// ```
+/// use medic::checks::env_var_check;
+/// use medic::checks::EnvExpectation;
+/// use medic::checks::DEFAULT_REDACT_PATTERNS;
+/// use medic::CheckResult;
+///
+/// let check = env_var_check(
+///     "chezmoi-override",
+///     "CHEZMOI_MODIFY_MANAGER_ASSUME_CHEZMOI_VERSION",
+///     EnvExpectation::Unset,
+///     CheckResult::Error,
+///     CheckResult::Warning,
+///     DEFAULT_REDACT_PATTERNS,
+/// );
+/// ```
+///
+/// * `name`: Name of check (for display)
+/// * `var`: Name of the environment variable
+/// * `expectation`: What is expected of the variable
+/// * `missing`: Result to report if the variable should be set but isn't
+/// * `mismatch`: Result to report if the variable is set, but the expectation
+///   doesn't hold
+/// * `redact_patterns`: Patterns for names whose values are not shown (such
+///   as [`DEFAULT_REDACT_PATTERNS`])
+pub fn env_var_check(
+    name: &'static str,
+    var: &'static str,
+    expectation: EnvExpectation,
+    missing: CheckResult,
+    mismatch: CheckResult,
+    redact_patterns: &[&str],
+) -> Check {
+    let patterns: Vec<String> = redact_patterns
+        .iter()
+        .map(|pattern| (*pattern).to_owned())
+        .collect();
+    Check::new(name, move || {
+        let value = std::env::var_os(var).map(|value| value.to_string_lossy().into_owned());
+        Ok(evaluate_env_var(
+            var,
+            value.as_deref(),
+            expectation,
+            missing,
+            mismatch,
+            &patterns,
+        ))
+    })
+}
+
+/// Compare the value of a variable (`None` if not set) with the expectation
+///
+/// See [`env_var_check`] for `missing` and `mismatch`.
+pub(crate) fn evaluate_env_var(
+    var: &str,
+    value: Option<&str>,
+    expectation: EnvExpectation,
+    missing: CheckResult,
+    mismatch: CheckResult,
+    patterns: &[impl AsRef<str>],
+) -> (CheckResult, String) {
+    let Some(value) = value else {
+        return match expectation {
+            EnvExpectation::Unset => (CheckResult::Ok, format!("{var} is not set")),
+            EnvExpectation::Set => (missing, format!("{var} is not set")),
+            EnvExpectation::OneOf(allowed) => (
+                missing,
+                format!("{var} is not set (expected one of: {})", allowed.join(", ")),
+            ),
+            EnvExpectation::Predicate { description, .. } => (
+                missing,
+                format!("{var} is not set (expected {description})"),
+            ),
+        };
+    };
+    let shown = display_var(var, value, patterns);
+    match expectation {
+        EnvExpectation::Unset => (mismatch, format!("{shown} (expected it to not be set)")),
+        EnvExpectation::Set => (CheckResult::Ok, shown),
+        EnvExpectation::OneOf(allowed) if allowed.contains(&value) => (CheckResult::Ok, shown),
+        EnvExpectation::OneOf(allowed) => (
+            mismatch,
+            format!("{shown} (expected one of: {})", allowed.join(", ")),
+        ),
+        EnvExpectation::Predicate { test, .. } if test(value) => (CheckResult::Ok, shown),
+        EnvExpectation::Predicate { description, .. } => {
+            (mismatch, format!("{shown} (expected {description})"))
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Check for fonts being available on the system
+
+use crate::Check;
+use crate::CheckResult;
+
+/// Check that a font family is installed on the system
+///
+/// This loads the system font set (using the fontconfig configuration on
+/// Linux) and looks for a face in the given family (case-insensitive). The
+/// font file that provides it is included in the message when found.
+///
+/// The family name is also used as the name of the check.
+pub fn font_available(family: &'static str) -> Check {
+    Check::new(family, move || {
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+        let face = db.faces().find(|face| {
+            face.families
+                .iter()
+                .any(|(face_family, _)| face_family.eq_ignore_ascii_case(family))
+        });
+        Ok(match face {
+            Some(face) => {
+                let source = match &face.source {
+                    fontdb::Source::File(path) => path.display().to_string(),
+                    _ => "<in-memory>".to_owned(),
+                };
+                (CheckResult::Ok, format!("Font {family} found at {source}"))
+            }
+            None => (
+                CheckResult::Warning,
+                format!("Font {family} not found ({} fonts available)", db.len()),
+            ),
+        })
+    })
+}
@@ -0,0 +1,175 @@
+//! Checks related to the file system
+
+use crate::Check;
+use crate::CheckResult;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Walk a directory tree, returning the number of entries or the first
+/// path that couldn't be read
+///
+/// Symlinks are not followed (to avoid loops), but are checked for being
+/// readable if they point to a file.
+fn walk_readable(root: &Path) -> Result<usize, (PathBuf, std::io::Error)> {
+    let mut pending = vec![root.to_path_buf()];
+    let mut count = 0;
+    while let Some(path) = pending.pop() {
+        count += 1;
+        let metadata = std::fs::symlink_metadata(&path).map_err(|err| (path.clone(), err))?;
+        if metadata.is_dir() {
+            let entries = std::fs::read_dir(&path).map_err(|err| (path.clone(), err))?;
+            for entry in entries {
+                pending.push(entry.map_err(|err| (path.clone(), err))?.path());
+            }
+        } else if metadata.is_file() || path.is_file() {
+            std::fs::File::open(&path).map_err(|err| (path.clone(), err))?;
+        }
+    }
+    Ok(count)
+}
+
+/// Check that a whole directory tree is readable
+///
+/// This catches the case where e.g. a config directory is readable, but some
+/// subdirectory or file within it is not. The first unreadable path found is
+/// reported as an [`CheckResult::Error`].
+///
+/// * `root`: Directory to check
+/// * `name`: Name of check (for display)
+pub fn readable_tree(root: impl Into<PathBuf>, name: &'static str) -> Check {
+    let root = root.into();
+    Check::new(name, move || {
+        Ok(match walk_readable(&root) {
+            Ok(count) => (
+                CheckResult::Ok,
+                format!("All {count} entries in {} are readable", root.display()),
+            ),
+            Err((path, err)) => (
+                CheckResult::Error,
+                format!("Failed to read {}: {err}", path.display()),
+            ),
+        })
+    })
+}
+
+/// Format a number of bytes for humans (using binary prefixes)
+#[cfg(feature = "free-space")]
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
+
+/// Check that there is enough free space on the file system containing a path
+///
+/// The result is [`CheckResult::Warning`] if less than `min_bytes` is
+/// available (to unprivileged users), and [`CheckResult::Error`] if the free
+/// space couldn't be determined (e.g. because the path doesn't exist).
+///
+/// * `path`: Path on the file system to check
+/// * `min_bytes`: Minimum free space required
+/// * `name`: Name of check (for display)
+#[cfg(feature = "free-space")]
+pub fn free_space_check(path: impl Into<PathBuf>, min_bytes: u64, name: &'static str) -> Check {
+    let path = path.into();
+    Check::new(name, move || {
+        Ok(match fs4::available_space(&path) {
+            Ok(available) if available >= min_bytes => (
+                CheckResult::Ok,
+                format!("{} free at {}", format_bytes(available), path.display()),
+            ),
+            Ok(available) => (
+                CheckResult::Warning,
+                format!(
+                    "Only {} free at {} (want at least {})",
+                    format_bytes(available),
+                    path.display(),
+                    format_bytes(min_bytes)
+                ),
+            ),
+            Err(err) => (
+                CheckResult::Error,
+                format!("Failed to get free space at {}: {err}", path.display()),
+            ),
+        })
+    })
+}
+
+/// Try to write to a file, or to create a file in a directory
+fn try_write(path: &Path) -> Result<(), std::io::Error> {
+    if path.is_dir() {
+        let probe = path.join(format!(".medic-write-test-{}", std::process::id()));
+        std::fs::File::create(&probe)?;
+        std::fs::remove_file(&probe)
+    } else {
+        std::fs::OpenOptions::new().append(true).open(path)?;
+        Ok(())
+    }
+}
+
+/// Check that a file or directory is writable
+///
+/// For directories, this is tested by creating (and removing) a temporary
+/// file in it, as permission bits alone don't tell the whole story (ACLs,
+/// read-only mounts, etc). Files are opened for appending, without changing
+/// them. Failures are reported as [`CheckResult::Error`].
+///
+/// * `path`: File or directory to check
+/// * `name`: Name of check (for display)
+pub fn writable_check(path: impl Into<PathBuf>, name: &'static str) -> Check {
+    let path = path.into();
+    Check::new(name, move || {
+        Ok(match try_write(&path) {
+            Ok(()) => (CheckResult::Ok, format!("{} is writable", path.display())),
+            Err(err) => (
+                CheckResult::Error,
+                format!("{} is not writable: {err}", path.display()),
+            ),
+        })
+    })
+}
+
+/// Check that a file or directory exists
+///
+/// * `path`: Path to check
+/// * `severity_if_missing`: Result to report if the path doesn't exist (for
+///   example [`CheckResult::Info`] for an optional config file)
+/// * `name`: Name of check (for display)
+pub fn exists_check(
+    path: impl Into<PathBuf>,
+    severity_if_missing: CheckResult,
+    name: &'static str,
+) -> Check {
+    let path = path.into();
+    Check::new(name, move || {
+        Ok(match std::fs::metadata(&path) {
+            Ok(metadata) => {
+                let kind = if metadata.is_dir() {
+                    "Directory"
+                } else {
+                    "File"
+                };
+                (CheckResult::Ok, format!("{kind} {} exists", path.display()))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => (
+                severity_if_missing,
+                format!("{} does not exist", path.display()),
+            ),
+            Err(err) => (
+                CheckResult::Error,
+                format!("Failed to access {}: {err}", path.display()),
+            ),
+        })
+    })
+}
@@ -0,0 +1,80 @@
+//! Heuristic detection of how the program was installed
+
+use crate::Check;
+use crate::CheckResult;
+use std::path::Path;
+
+/// Guess the install source from the path to the executable
+///
+/// * `cargo_home`: Value of `CARGO_HOME`, if set
+/// * `target_dir`: Value of `CARGO_TARGET_DIR`, if set
+///
+/// The order matters, more specific patterns must come first.
+pub(crate) fn classify_install_path(
+    path: &Path,
+    cargo_home: Option<&str>,
+    target_dir: Option<&str>,
+) -> &'static str {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let under = |dir: Option<&str>, suffix: &str| {
+        dir.is_some_and(|dir| {
+            let dir = dir.replace('\\', "/");
+            path.starts_with(&format!("{}/{suffix}", dir.trim_end_matches('/')))
+        })
+    };
+    if under(cargo_home, "bin/") || path.contains("/.cargo/bin/") {
+        "cargo install"
+    } else if under(target_dir, "") || in_target_dir(&path) {
+        "built from source"
+    } else if path.starts_with("/opt/homebrew/")
+        || path.starts_with("/usr/local/Cellar/")
+        || path.starts_with("/home/linuxbrew/.linuxbrew/")
+    {
+        "Homebrew"
+    } else if path.starts_with("/nix/store/") || path.contains("/.nix-profile/") {
+        "Nix"
+    } else if path.starts_with("/snap/") {
+        "Snap"
+    } else if path.contains("/scoop/") {
+        "Scoop"
+    } else if path.starts_with("/usr/local/") {
+        "manual install"
+    } else if path.starts_with("/usr/") || path.starts_with("/bin/") || path.starts_with("/sbin/") {
+        "system package manager"
+    } else {
+        "unknown"
+    }
+}
+
+/// Check if the path is inside a cargo target directory
+///
+/// This is a `target` directory with at least one more level (the profile,
+/// such as `debug` or a custom profile, possibly below a target triple)
+/// before the executable.
+fn in_target_dir(path: &str) -> bool {
+    let components: Vec<_> = path.split('/').collect();
+    components
+        .iter()
+        .position(|component| *component == "target")
+        .is_some_and(|idx| components.len() > idx + 2)
+}
+
+/// Report (as a heuristic) which package manager installed the program
+///
+/// This is based purely on the location of the executable (e.g. `/usr/bin`
+/// vs `~/.cargo/bin` vs `/opt/homebrew`), so it may well be wrong.
+pub const CHECK_INSTALL_SOURCE: Check = Check::new_const("install-source", || {
+    let exe = std::env::current_exe()?;
+    Ok((
+        CheckResult::Info,
+        format!(
+            "{} (heuristic, based on {})",
+            classify_install_path(
+                &exe,
+                std::env::var("CARGO_HOME").ok().as_deref(),
+                std::env::var("CARGO_TARGET_DIR").ok().as_deref()
+            ),
+            exe.display()
+        ),
+    ))
+});
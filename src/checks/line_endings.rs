@@ -0,0 +1,84 @@
+//! Check for the line ending style used in a file
+
+use crate::Check;
+use crate::CheckResult;
+use std::path::PathBuf;
+
+/// Line ending style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix style (`\n`)
+    Lf,
+    /// Windows style (`\r\n`)
+    CrLf,
+}
+
+impl LineEnding {
+    /// The native line ending style of the platform the code was compiled for
+    pub const fn native() -> Self {
+        if cfg!(windows) {
+            Self::CrLf
+        } else {
+            Self::Lf
+        }
+    }
+}
+
+impl std::fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lf => f.write_str("LF"),
+            Self::CrLf => f.write_str("CRLF"),
+        }
+    }
+}
+
+/// Count the number of LF and CRLF line endings in the data
+fn count_line_endings(data: &[u8]) -> (usize, usize) {
+    let mut lf = 0;
+    let mut crlf = 0;
+    for (idx, _) in data.iter().enumerate().filter(|(_, b)| **b == b'\n') {
+        if idx > 0 && data[idx - 1] == b'\r' {
+            crlf += 1;
+        } else {
+            lf += 1;
+        }
+    }
+    (lf, crlf)
+}
+
+/// Classify the predominant line ending in the data
+fn classify_line_endings(data: &[u8], expected: LineEnding) -> (CheckResult, String) {
+    let (lf, crlf) = count_line_endings(data);
+    let detected = match (lf, crlf) {
+        (0, 0) => return (CheckResult::Info, "No line endings found".to_owned()),
+        (lf, crlf) if crlf > lf => LineEnding::CrLf,
+        _ => LineEnding::Lf,
+    };
+    let counts = format!("(LF: {lf}, CRLF: {crlf})");
+    if detected == expected {
+        (CheckResult::Ok, format!("Detected {detected} {counts}"))
+    } else {
+        (
+            CheckResult::Warning,
+            format!("Detected {detected}, expected {expected} {counts}"),
+        )
+    }
+}
+
+/// Check that a file predominantly uses the expected line ending style
+///
+/// Mismatched line endings are frequently caused by the git `core.autocrlf`
+/// setting when checking out files on Windows.
+///
+/// * `path`: File to inspect
+/// * `expected`: Line ending style the file should use
+/// * `name`: Name of check (for display)
+pub fn line_endings(path: impl Into<PathBuf>, expected: LineEnding, name: &'static str) -> Check {
+    let path = path.into();
+    Check::new(name, move || {
+        let data = std::fs::read(&path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        Ok(classify_line_endings(&data, expected))
+    })
+}
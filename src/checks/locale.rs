@@ -0,0 +1,92 @@
+//! Check for locale and terminal environment
+
+use crate::Check;
+use crate::CheckResult;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+
+/// Report the locale and terminal environment, warning on non-UTF-8 locales
+///
+/// This shows `LANG` and any `LC_*` variables, `TERM` and the width of the
+/// terminal (if stdout is one). The effective character encoding is taken
+/// from `LC_ALL`, `LC_CTYPE` or `LANG` (in that order). The result is
+/// [`CheckResult::Warning`] if that is not UTF-8 (such as the `C` and `POSIX`
+/// locales), as that commonly mangles output.
+///
+/// Windows doesn't use these variables, so there the result is at most
+/// [`CheckResult::Info`].
+pub const CHECK_LOCALE: Check = Check::new_const("locale", || {
+    let width = terminal_size::terminal_size_of(std::io::stdout())
+        .map(|(terminal_size::Width(width), _)| width);
+    Ok(report_locale(lossy_vars(std::env::vars_os()), width))
+});
+
+/// Convert environment variables to strings, replacing invalid Unicode
+///
+/// [`std::env::vars`] panics on such variables, which a diagnostic tool
+/// mustn't do just because of an unrelated variable.
+pub(crate) fn lossy_vars(
+    vars: impl IntoIterator<Item = (OsString, OsString)>,
+) -> impl Iterator<Item = (String, String)> {
+    vars.into_iter().map(|(name, value)| {
+        (
+            name.to_string_lossy().into_owned(),
+            value.to_string_lossy().into_owned(),
+        )
+    })
+}
+
+/// Report the locale based on the given environment variables and terminal
+/// width (`None` if stdout is not a terminal)
+pub(crate) fn report_locale(
+    vars: impl IntoIterator<Item = (String, String)>,
+    terminal_width: Option<u16>,
+) -> (CheckResult, String) {
+    let vars: BTreeMap<String, String> = vars.into_iter().collect();
+    let mut lines = vec![];
+    match vars.get("LANG") {
+        Some(lang) => lines.push(format!("LANG={lang}")),
+        None => lines.push("LANG not set".to_owned()),
+    }
+    lines.extend(
+        vars.iter()
+            .filter(|(name, _)| name.starts_with("LC_"))
+            .map(|(name, value)| format!("{name}={value}")),
+    );
+    match vars.get("TERM") {
+        Some(term) => lines.push(format!("TERM={term}")),
+        None => lines.push("TERM not set".to_owned()),
+    }
+    match terminal_width {
+        Some(width) => lines.push(format!("Terminal width: {width}")),
+        None => lines.push("stdout is not a terminal".to_owned()),
+    }
+
+    let effective = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| vars.get(*name).filter(|value| !value.is_empty()));
+    let result = match effective {
+        Some(locale) if is_utf8(locale) => CheckResult::Ok,
+        _ if cfg!(windows) => CheckResult::Info,
+        Some(locale) => {
+            lines.push(format!(
+                "Locale {locale} is not UTF-8, output may be garbled"
+            ));
+            CheckResult::Warning
+        }
+        None => {
+            lines.push("No locale set (defaults to C), output may be garbled".to_owned());
+            CheckResult::Warning
+        }
+    };
+    (result, lines.join("\n"))
+}
+
+/// Check if a locale name (such as `en_US.UTF-8`) uses UTF-8
+pub(crate) fn is_utf8(locale: &str) -> bool {
+    // Strip any modifier (e.g. `@euro`), then look at the codeset
+    let locale = locale.split('@').next().unwrap_or_default();
+    locale.rsplit_once('.').is_some_and(|(_, codeset)| {
+        codeset.eq_ignore_ascii_case("utf-8") || codeset.eq_ignore_ascii_case("utf8")
+    })
+}
@@ -0,0 +1,153 @@
+//! Checks for executables in `PATH`
+
+use crate::Check;
+use crate::CheckResult;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Candidate file names for an executable (handling `PATHEXT` on Windows)
+fn candidate_names(binary: &str) -> Vec<String> {
+    if cfg!(windows) {
+        let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into());
+        let mut names: Vec<String> = pathext
+            .split(';')
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| format!("{binary}{}", ext.to_ascii_lowercase()))
+            .collect();
+        // Allow specifying the extension explicitly
+        if Path::new(binary).extension().is_some() {
+            names.insert(0, binary.to_owned());
+        }
+        names
+    } else {
+        vec![binary.to_owned()]
+    }
+}
+
+/// Check if a path is an executable file
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Find all matches for an executable in `PATH`, in order of priority
+pub(crate) fn find_in_path(binary: &str) -> Vec<PathBuf> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return vec![];
+    };
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let names = candidate_names(binary);
+    std::env::split_paths(&path)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .filter(|candidate| is_executable(candidate))
+        .map(|candidate| cwd.join(candidate))
+        .collect()
+}
+
+/// Check that an executable can be found in `PATH`
+///
+/// This searches `PATH` the same way the platform does (including `PATHEXT`
+/// on Windows). If found, the result is [`CheckResult::Ok`] with the absolute
+/// path of the first match (and the number of matches, if more than one).
+/// Otherwise the result is [`CheckResult::Warning`].
+///
+/// The binary name is also used as the name of the check.
+pub fn check_in_path(binary: &'static str) -> Check {
+    Check::new(binary, move || {
+        let matches = find_in_path(binary);
+        Ok(match matches.as_slice() {
+            [] => (
+                CheckResult::Warning,
+                format!("{binary} not found in PATH, make sure it is installed"),
+            ),
+            [found] => (
+                CheckResult::Ok,
+                format!("{binary} found at {}", found.display()),
+            ),
+            [found, ..] => (
+                CheckResult::Ok,
+                format!(
+                    "{binary} found at {} ({} matches in PATH, using the first)",
+                    found.display(),
+                    matches.len()
+                ),
+            ),
+        })
+    })
+}
+
+/// Check that an executable can be found in `PATH`, and report its version
+///
+/// The executable is located the same way as [`check_in_path`]. If
+/// `version_args` is given (such as `&["--version"]`), the executable is run
+/// with those arguments and the first non-empty line of output is included in
+/// the message. A failure to get the version is reported as
+/// [`CheckResult::Warning`].
+///
+/// * `name`: Name of check (for display)
+/// * `binary`: Name of the executable to look for
+/// * `version_args`: Arguments to get the version, or `None` to not run it
+/// * `missing`: Result to report if the executable is not found (for example
+///   [`CheckResult::Info`] for optional tools)
+pub fn executable_check(
+    name: &'static str,
+    binary: &'static str,
+    version_args: Option<&'static [&'static str]>,
+    missing: CheckResult,
+) -> Check {
+    Check::new(name, move || {
+        let Some(found) = find_in_path(binary).into_iter().next() else {
+            return Ok((missing, format!("{binary} not found in PATH")));
+        };
+        let Some(args) = version_args else {
+            return Ok((
+                CheckResult::Ok,
+                format!("{binary} found at {}", found.display()),
+            ));
+        };
+        Ok(match probe_version(&found, args) {
+            Ok(version) => (
+                CheckResult::Ok,
+                format!("{binary} found at {}. Version: {version}", found.display()),
+            ),
+            Err(err) => (
+                CheckResult::Warning,
+                format!(
+                    "{binary} found at {}, but failed to get version: {err}",
+                    found.display()
+                ),
+            ),
+        })
+    })
+}
+
+/// Run an executable to get its version string
+fn probe_version(binary: &Path, args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new(binary)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        return Err(format!("exited with {}", output.status));
+    }
+    // Some programs (such as java) print the version on stderr
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| "no output".to_owned())
+}
@@ -0,0 +1,40 @@
+//! Check for macOS Gatekeeper app translocation
+
+use crate::Check;
+use crate::CheckResult;
+use std::path::Path;
+
+/// Warn if the program is running from a translocated path (macOS only)
+///
+/// Gatekeeper app translocation runs quarantined binaries from a random
+/// read-only location, which breaks assumptions about relative paths. The
+/// usual fix is to remove the quarantine attribute with
+/// `xattr -d com.apple.quarantine <path>`.
+///
+/// On other platforms this check is [`CheckResult::Skipped`].
+pub const CHECK_TRANSLOCATION: Check = Check::new_const("translocation", || {
+    if !cfg!(target_os = "macos") {
+        return Ok((
+            CheckResult::Skipped,
+            "Not applicable on this platform".to_owned(),
+        ));
+    }
+    let exe = std::env::current_exe()?;
+    if is_translocated(&exe) {
+        Ok((
+            CheckResult::Warning,
+            format!(
+                "Running from translocated path {} (remove the quarantine attribute to fix)",
+                exe.display()
+            ),
+        ))
+    } else {
+        Ok((CheckResult::Ok, "Not translocated".to_owned()))
+    }
+});
+
+/// Check if `path` is inside a Gatekeeper translocation directory
+pub(crate) fn is_translocated(path: &Path) -> bool {
+    path.components()
+        .any(|component| component.as_os_str() == "AppTranslocation")
+}
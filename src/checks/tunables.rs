@@ -0,0 +1,77 @@
+//! Checks for kernel tunables and similar system settings
+
+use crate::Check;
+use crate::CheckResult;
+
+/// Compare the current value of a setting with the expected value (if any)
+///
+/// The value is reported as [`CheckResult::Info`] (as it is mostly of interest
+/// for troubleshooting) if it matches or no expected value is given, and as
+/// [`CheckResult::Warning`] (showing both values) if it differs.
+fn compare_setting(current: &str, expected: Option<&str>) -> (CheckResult, String) {
+    match expected {
+        Some(expected) if expected != current => (
+            CheckResult::Warning,
+            format!("Current value: {current}, expected: {expected}"),
+        ),
+        _ => (CheckResult::Info, current.to_owned()),
+    }
+}
+
+/// Report the value of a sysctl (read from `/proc/sys`)
+///
+/// The current value is reported as [`CheckResult::Info`], or as
+/// [`CheckResult::Warning`] if it differs from `expected`.
+///
+/// * `name`: Name of the sysctl in dotted form (e.g. `vm.max_map_count`), also
+///   used as the name of the check.
+/// * `expected`: If given, warn when the current value differs (showing both).
+///
+/// Whitespace between fields (as used by multi-valued sysctls) is normalised
+/// to a single space before comparison.
+#[cfg(target_os = "linux")]
+pub fn sysctl(name: &'static str, expected: Option<String>) -> Check {
+    Check::new(name, move || {
+        let path = std::path::Path::new("/proc/sys").join(name.replace('.', "/"));
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        let current = contents.split_whitespace().collect::<Vec<_>>().join(" ");
+        Ok(compare_setting(&current, expected.as_deref()))
+    })
+}
+
+/// Report the value of a Windows registry setting
+///
+/// The current value is reported as [`CheckResult::Info`], or as
+/// [`CheckResult::Warning`] if it differs from `expected`.
+///
+/// * `name`: Name of check (for display)
+/// * `key`: Path of the registry key, starting with the name of the hive (e.g.
+///   `HKEY_LOCAL_MACHINE\SYSTEM\CurrentControlSet\Control\FileSystem`). The
+///   short forms (`HKLM`, `HKCU`, etc.) are also accepted.
+/// * `value`: Name of the value in the key (e.g. `LongPathsEnabled`).
+/// * `expected`: If given, warn when the current value differs (showing both).
+#[cfg(windows)]
+pub fn registry_value(
+    name: &'static str,
+    key: &'static str,
+    value: &'static str,
+    expected: Option<String>,
+) -> Check {
+    Check::new(name, move || {
+        let (hive, subkey) = key.split_once('\\').unwrap_or((key, ""));
+        let hive = match hive.to_ascii_uppercase().as_str() {
+            "HKEY_CLASSES_ROOT" | "HKCR" => winreg::HKCR,
+            "HKEY_CURRENT_USER" | "HKCU" => winreg::HKCU,
+            "HKEY_LOCAL_MACHINE" | "HKLM" => winreg::HKLM,
+            "HKEY_USERS" | "HKU" => winreg::HKU,
+            "HKEY_CURRENT_CONFIG" | "HKCC" => winreg::HKCC,
+            _ => return Err(format!("Unknown registry hive: {hive}").into()),
+        };
+        let current = hive
+            .open_subkey(subkey)
+            .and_then(|key| key.get_raw_value(value))
+            .map_err(|err| format!("Failed to read {key}\\{value}: {err}"))?;
+        Ok(compare_setting(&current.to_string(), expected.as_deref()))
+    })
+}
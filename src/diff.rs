@@ -0,0 +1,171 @@
+//! Comparison of check results between two runs
+
+use crate::render::pad;
+use crate::CheckOutcome;
+use crate::CheckResult;
+use crate::MedicError;
+use std::io::Write;
+use unicode_width::UnicodeWidthStr;
+
+/// A saved set of check results (such as from a previous run)
+///
+/// Create it from the outcomes of a run with [`SavedReport::new`], or (with
+/// the `serde` feature) load the output of `medic_json` or `render_json` with
+/// `SavedReport::from_json`. Compare two reports with [`diff_reports`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedReport {
+    /// The results, in the order they were run
+    pub checks: Vec<SavedCheck>,
+}
+
+/// The result of a single check in a [`SavedReport`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedCheck {
+    /// Name of the check
+    pub name: String,
+    /// Severity level
+    pub result: CheckResult,
+    /// Message describing the situation
+    pub message: String,
+}
+
+impl SavedReport {
+    /// Create a report from the outcomes of a run
+    pub fn new(outcomes: &[CheckOutcome]) -> Self {
+        Self {
+            checks: outcomes
+                .iter()
+                .map(|outcome| SavedCheck {
+                    name: outcome.name.to_owned(),
+                    result: outcome.result,
+                    message: outcome.message.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Load a report written by `medic_json` or `render_json`
+    ///
+    /// Only the name, result and message of each check are used, other fields
+    /// are ignored. Returns [`MedicError::InvalidReport`] if the input isn't
+    /// such a report.
+    #[cfg(feature = "serde")]
+    pub fn from_json(input: impl std::io::Read) -> Result<Self, MedicError> {
+        Ok(serde_json::from_reader(input)?)
+    }
+}
+
+/// A difference between two reports, see [`diff_reports`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReportChange {
+    /// The check is only in the new report
+    Added(SavedCheck),
+    /// The check is only in the old report
+    Removed(SavedCheck),
+    /// The result or message of the check changed
+    Changed {
+        /// The check in the old report
+        old: SavedCheck,
+        /// The check in the new report
+        new: SavedCheck,
+    },
+}
+
+/// Compare two reports (such as "yesterday" and "today")
+///
+/// Checks are matched by name. Checks that are identical in both reports are
+/// left out. Added and changed checks are listed in the order of the new
+/// report, followed by the removed checks in the order of the old report.
+pub fn diff_reports(old: &SavedReport, new: &SavedReport) -> Vec<ReportChange> {
+    let find = |report: &SavedReport, name: &str| {
+        report
+            .checks
+            .iter()
+            .find(|check| check.name == name)
+            .cloned()
+    };
+    let mut changes: Vec<_> = new
+        .checks
+        .iter()
+        .filter_map(|check| match find(old, &check.name) {
+            None => Some(ReportChange::Added(check.clone())),
+            Some(old) if old != *check => Some(ReportChange::Changed {
+                old,
+                new: check.clone(),
+            }),
+            Some(_) => None,
+        })
+        .collect();
+    changes.extend(
+        old.checks
+            .iter()
+            .filter(|check| find(new, &check.name).is_none())
+            .cloned()
+            .map(ReportChange::Removed),
+    );
+    changes
+}
+
+/// Render the differences between two reports (as returned by
+/// [`diff_reports`])
+///
+/// Results are coloured the same way as in the table (see
+/// [Colours](crate#colours)).
+///
+/// ```text
+/// Added    proxy    Warning  HTTPS_PROXY is set but HTTP_PROXY is not
+/// Changed  build    Ok -> Warning
+///                   was: Official release
+///                   now: Github CI build (not official release)
+/// Removed  fonts    Ok       Font found
+/// ```
+pub fn render_diff(output: &mut impl Write, changes: &[ReportChange]) -> Result<(), MedicError> {
+    if changes.is_empty() {
+        writeln!(output, "No changes")?;
+        return Ok(());
+    }
+    let name_width = changes
+        .iter()
+        .map(|change| match change {
+            ReportChange::Added(check) | ReportChange::Removed(check) => check.name.width(),
+            ReportChange::Changed { new, .. } => new.name.width(),
+        })
+        .max()
+        .unwrap_or_default();
+    // Continuation lines of messages are indented past the change and name
+    // (both result names and change kinds are at most 7 characters)
+    let indent = " ".repeat(7 + 2 + name_width + 2);
+    let indent_lines =
+        |message: &str, extra: usize| message.replace('\n', &format!("\n{indent}{:extra$}", ""));
+
+    for change in changes {
+        let (kind, check) = match change {
+            ReportChange::Added(check) => ("Added", check),
+            ReportChange::Removed(check) => ("Removed", check),
+            ReportChange::Changed { old, new } => {
+                let name = pad(&new.name, name_width);
+                if old.result == new.result {
+                    writeln!(output, "Changed  {name}  {}", new.result)?;
+                } else {
+                    writeln!(output, "Changed  {name}  {} -> {}", old.result, new.result)?;
+                }
+                if old.message != new.message {
+                    writeln!(output, "{indent}was: {}", indent_lines(&old.message, 5))?;
+                    writeln!(output, "{indent}now: {}", indent_lines(&new.message, 5))?;
+                }
+                continue;
+            }
+        };
+        writeln!(
+            output,
+            "{kind: <7}  {}  {: <7}  {}",
+            pad(&check.name, name_width),
+            check.result,
+            indent_lines(&check.message, 9)
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Mapping of results to process exit codes
+
+use crate::medic;
+use crate::summary;
+use crate::Check;
+use crate::CheckResult;
+use std::process::ExitCode;
+
+/// Which results should make the process fail
+///
+/// Used with [`CheckResult::to_exit_code`] and [`medic_main`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExitPolicy {
+    /// Fail on warnings and errors (same as [`CheckResult::exit_code`])
+    #[default]
+    FailOnWarning,
+    /// Fail on errors only, warnings are still reported in the summary
+    FailOnError,
+    /// Always succeed
+    Never,
+}
+
+impl CheckResult {
+    /// Get the process exit code for this level (when it is the worst level
+    /// found)
+    ///
+    /// The mapping is stable:
+    ///
+    /// | Result                     | Exit code |
+    /// |----------------------------|-----------|
+    /// | Skipped, Ok, Info          | 0         |
+    /// | Warning                    | 1         |
+    /// | Error, Fatal               | 2         |
+    ///
+    /// Use [`CheckResult::to_exit_code`] or [`threshold_exit_code`] if
+    /// warnings should not fail the process. They use this mapping for the
+    /// failing exit code.
+    ///
+    /// ```no_run
+    /// let worst = medic::medic(&mut anstream::stdout(), [medic::checks::CHECK_HOST].iter())?;
+    /// std::process::exit(worst.exit_code());
+    /// # Ok::<(), medic::MedicError>(())
+    /// ```
+    pub const fn exit_code(&self) -> i32 {
+        match self {
+            Self::Skipped | Self::Ok | Self::Info => 0,
+            Self::Warning => 1,
+            Self::Error | Self::Fatal => 2,
+        }
+    }
+
+    /// Get the process exit code for this level (when it is the worst level
+    /// found) according to the policy
+    ///
+    /// This is [`threshold_exit_code`] with the threshold of the policy, so
+    /// failing exit codes follow the mapping of [`CheckResult::exit_code`].
+    pub fn to_exit_code(self, policy: ExitPolicy) -> ExitCode {
+        match policy {
+            ExitPolicy::FailOnWarning => threshold_exit_code(self, Self::Warning),
+            ExitPolicy::FailOnError => threshold_exit_code(self, Self::Error),
+            ExitPolicy::Never => ExitCode::SUCCESS,
+        }
+    }
+}
+
+/// Get the process exit code for the worst level found
+///
+/// Returns [`ExitCode::SUCCESS`] if `worst_issues_found` is below `threshold`,
+/// otherwise the code from [`CheckResult::exit_code`] (at least 1, so that it
+/// is a failure even for a threshold below [`CheckResult::Warning`]). This is
+/// independent of the threshold used for the summary (see
+/// [`summary_with_threshold`](crate::summary_with_threshold)).
+pub fn threshold_exit_code(worst_issues_found: CheckResult, threshold: CheckResult) -> ExitCode {
+    if worst_issues_found >= threshold {
+        ExitCode::from(worst_issues_found.exit_code().max(1) as u8)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Perform environment sanity check on stdout, print the summary and get the
+/// exit code
+///
+/// This is suitable for calling directly from a `--doctor` subcommand:
+///
+/// ```no_run
+/// use medic::checks;
+/// use medic::ExitPolicy;
+///
+/// fn main() -> std::process::ExitCode {
+///     let checks = [checks::CHECK_RUSTC_VERSION, checks::CHECK_HOST];
+///     medic::medic_main(checks.iter(), ExitPolicy::FailOnError)
+/// }
+/// ```
+///
+/// Colour codes are stripped if stdout isn't a terminal. If writing the
+/// output fails, the error is printed to stderr and the exit code is
+/// failure.
+pub fn medic_main<'iter>(
+    checks: impl Iterator<Item = &'iter Check>,
+    policy: ExitPolicy,
+) -> ExitCode {
+    let mut stdout = anstream::stdout();
+    let worst = medic(&mut stdout, checks).and_then(|worst| {
+        summary(&mut stdout, worst)?;
+        Ok(worst)
+    });
+    match worst {
+        Ok(worst) => worst.to_exit_code(policy),
+        Err(err) => {
+            eprintln!("Failed to write diagnostics: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Automatic fixing of problems found by checks
+
+use crate::render_table;
+use crate::run_checks;
+use crate::worst_result;
+use crate::Check;
+use crate::CheckOutcome;
+use crate::CheckResult;
+use crate::MedicError;
+use std::io::Write;
+
+/// Function that attempts to fix the problem found by a check
+///
+/// Returns a description of what was done.
+pub(crate) type FixFn =
+    Box<dyn Fn() -> Result<String, Box<dyn std::error::Error + Send + Sync>> + Send + Sync>;
+
+/// Perform environment sanity check, then try to fix any problems found
+///
+/// All checks are run and shown as with [`medic`](crate::medic). Then, for
+/// each check with a result of [`CheckResult::Warning`] or worse that has a
+/// fix (see [`Check::with_fix`]), `confirm` is called with the outcome, and
+/// the fix is applied if it returns `true`. Finally the checks are run again
+/// and shown, so that the status before and after can be compared.
+///
+/// With `dry_run`, the fixes that would be applied are listed without calling
+/// `confirm` or changing anything, and the checks are not run again.
+///
+/// Returns the worst level found in the last run (which can be passed to
+/// [`summary`](crate::summary))
+pub fn medic_fix<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter Check>,
+    dry_run: bool,
+    mut confirm: impl FnMut(&CheckOutcome) -> bool,
+) -> Result<CheckResult, MedicError> {
+    let checks: Vec<_> = checks.collect();
+    let before = run_checks(checks.iter().copied());
+    render_table(output, &before)?;
+
+    let fixable: Vec<_> = checks
+        .iter()
+        .zip(&before)
+        .filter(|(_, outcome)| outcome.result >= CheckResult::Warning)
+        .filter_map(|(check, outcome)| check.fix.as_ref().map(|fix| (fix, outcome)))
+        .collect();
+    if fixable.is_empty() {
+        writeln!(output, "\nNothing to fix")?;
+        return Ok(worst_result(&before));
+    }
+
+    writeln!(output)?;
+    for (fix, outcome) in fixable {
+        let name = outcome.name;
+        if dry_run {
+            writeln!(output, "Would fix {name}")?;
+        } else if !confirm(outcome) {
+            writeln!(output, "Not fixing {name} (declined)")?;
+        } else {
+            match fix() {
+                Ok(description) => writeln!(output, "Fixed {name}: {description}")?,
+                Err(err) => writeln!(output, "Failed to fix {name}: {err}")?,
+            }
+        }
+    }
+    if dry_run {
+        return Ok(worst_result(&before));
+    }
+
+    writeln!(output)?;
+    let after = run_checks(checks.into_iter());
+    render_table(output, &after)?;
+    Ok(worst_result(&after))
+}
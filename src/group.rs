@@ -0,0 +1,103 @@
+//! Grouping of checks into named sections
+
+use crate::run_checks;
+use crate::worst_result;
+use crate::Check;
+use crate::CheckOutcome;
+use crate::CheckResult;
+use crate::MedicError;
+use crate::RowRenderer;
+use crate::TableLayout;
+use crate::TableRenderer;
+use anstyle::Effects;
+use anstyle::Reset;
+use std::io::Write;
+
+/// A named group of checks (such as "installation" or "network")
+///
+/// Use with [`medic_grouped`] to show the checks in sections.
+#[derive(Debug)]
+pub struct CheckGroup {
+    name: &'static str,
+    checks: Vec<Check>,
+}
+
+impl CheckGroup {
+    /// Create a new group
+    ///
+    /// * `name`: Name of group (for display)
+    /// * `checks`: The checks in the group
+    pub fn new(name: &'static str, checks: impl IntoIterator<Item = Check>) -> Self {
+        Self {
+            name,
+            checks: checks.into_iter().collect(),
+        }
+    }
+
+    /// Get the name of the group
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Get the checks in the group
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+}
+
+/// Perform environment sanity check for groups of checks
+///
+/// Each group is shown as a section with a header giving the name of the group
+/// and the worst level found in it. Columns are aligned across all groups.
+/// Checks may depend on checks in other groups (see
+/// [`Check::depends_on`](crate::Check::depends_on)), as all the checks are run
+/// together.
+///
+/// ```text
+/// RESULT   CHECK          MESSAGE
+/// installation: Ok
+/// Ok       in-path        myprogram is in PATH at /usr/bin/myprogram
+///
+/// network: Warning
+/// Warning  proxy          HTTPS_PROXY is set but HTTP_PROXY is not
+/// ```
+///
+/// Returns the worst level found across all groups (which can be passed to
+/// [`summary`](crate::summary))
+pub fn medic_grouped<'iter>(
+    output: &mut impl Write,
+    groups: impl Iterator<Item = &'iter CheckGroup>,
+) -> Result<CheckResult, MedicError> {
+    let groups: Vec<&CheckGroup> = groups.collect();
+    // Run all checks at once, so that dependencies between groups resolve
+    let all = run_checks(groups.iter().flat_map(|group| group.checks.iter()));
+    let mut remaining = all.as_slice();
+    let sections: Vec<(&'static str, &[CheckOutcome])> = groups
+        .iter()
+        .map(|group| {
+            let (outcomes, rest) = remaining.split_at(group.checks.len());
+            remaining = rest;
+            (group.name, outcomes)
+        })
+        .collect();
+    let layout = TableLayout::new(&all);
+    let renderer = TableRenderer::new();
+
+    renderer.render_header(&layout, output)?;
+    for (idx, (name, outcomes)) in sections.into_iter().enumerate() {
+        if idx > 0 {
+            writeln!(output)?;
+        }
+        writeln!(
+            output,
+            "{}{name}{}: {}",
+            Effects::BOLD.render(),
+            Reset.render(),
+            worst_result(outcomes)
+        )?;
+        for outcome in outcomes {
+            renderer.render_row(outcome, &layout, output)?;
+        }
+    }
+    Ok(worst_result(&all))
+}
@@ -27,19 +27,189 @@
 //! ```
 //!
 //! The actual output is uses ANSI colour codes as well.
+//!
+//! ## Colours
+//!
+//! The functions writing to an output ([`medic`], [`summary`],
+//! [`render_table`], [`render_diff`] etc) always include ANSI colour codes
+//! (Markdown and JSON output excepted). They don't know where the output
+//! ends up, so it is up to the caller to strip the colour codes when they
+//! aren't wanted: write to an [`anstream::AutoStream`] (such as
+//! `anstream::stdout()`), which strips them if the destination isn't a
+//! terminal or if `NO_COLOR` is set. Alternatively use [`MedicReport`], which
+//! takes care of this and can be configured with [`MedicReport::color`].
+//!
+//! ## Post-processing results
+//!
+//! The [`medic`] family of functions both run the checks and render them. If
+//! you want to filter, sort, log or otherwise process the results yourself,
+//! use [`run_checks`] to get a list of [`CheckOutcome`] and then render them
+//! with [`render_table`] or [`render_with`]:
+//!
+//! ```
+//! use medic::CheckResult;
+//!
+//! let checks = [medic::checks::CHECK_RUSTC_VERSION, medic::checks::CHECK_HOST];
+//! let mut outcomes = medic::run_checks(checks.iter());
+//! for outcome in outcomes.iter().filter_map(medic::CheckOutcome::error) {
+//!     eprintln!("Check failed to run: {outcome}");
+//! }
+//! outcomes.sort_by_key(|outcome| std::cmp::Reverse(outcome.result));
+//! medic::render_table(&mut anstream::stdout(), &outcomes)?;
+//! medic::summary(&mut anstream::stdout(), medic::worst_result(&outcomes))?;
+//! # Ok::<(), medic::MedicError>(())
+//! ```
+//!
+//! ## Optional features
+//!
+//! * `async`: Enables `AsyncCheck` and `medic_async` (as well as
+//!   `medic_async_concurrent`) for checks that need to do asynchronous I/O.
+//!   This doesn't depend on any specific async runtime.
+//! * `fonts`: Enables `checks::font_available` for checking that a font
+//!   family is installed.
+//! * `free-space`: Enables `checks::free_space_check` for checking that
+//!   there is enough free disk space.
+//! * `log`: Enables `medic_log` and `log_outcomes` for emitting the results
+//!   as `log` records (for headless use, such as in a daemon).
+//! * `macros`: Enables the `check` attribute for declaring checks next to the
+//!   code they test, and `registered_checks` for collecting them.
+//! * `regex`: Enables `RedactionRules::pattern` for redacting custom
+//!   patterns from check messages.
+//! * `serde`: Enables `medic_json` and `render_json` for JSON output (which
+//!   can be loaded again with `SavedReport::from_json`), as well as
+//!   `serde::Serialize` for the result types.
+//! * `tracing`: Enables `medic_trace` and `trace_outcomes` for emitting the
+//!   results as `tracing` events.
 
 use anstyle::AnsiColor;
 use anstyle::Effects;
-use anstyle::Reset;
-use std::cmp::max;
+#[cfg(feature = "async")]
+pub use async_checks::medic_async;
+#[cfg(feature = "async")]
+pub use async_checks::medic_async_concurrent;
+#[cfg(feature = "async")]
+pub use async_checks::medic_async_with_options;
+#[cfg(feature = "async")]
+pub use async_checks::run_checks_async;
+#[cfg(feature = "async")]
+pub use async_checks::run_checks_async_concurrent;
+#[cfg(feature = "async")]
+pub use async_checks::run_checks_async_with;
+#[cfg(feature = "async")]
+pub use async_checks::AsyncCheck;
+#[cfg(feature = "async")]
+pub use async_checks::CheckFuture;
+pub use diff::diff_reports;
+pub use diff::render_diff;
+pub use diff::ReportChange;
+pub use diff::SavedCheck;
+pub use diff::SavedReport;
+pub use exit::medic_main;
+pub use exit::threshold_exit_code;
+pub use exit::ExitPolicy;
+pub use fix::medic_fix;
+pub use group::medic_grouped;
+pub use group::CheckGroup;
+#[cfg(feature = "log")]
+pub use logging::log_outcomes;
+#[cfg(feature = "log")]
+pub use logging::medic_log;
+#[cfg(feature = "tracing")]
+pub use logging::medic_trace;
+#[cfg(feature = "tracing")]
+pub use logging::trace_outcomes;
+/// Register a function as a check
+///
+/// The function must take no parameters and return the same as the function
+/// passed to [`Check::new`]. All registered checks can then be gathered with
+/// `registered_checks`, so that checks can be declared next to the code they
+/// test instead of in a central list:
+///
+/// ```
+/// use medic::CheckResult;
+///
+/// #[medic::check(name = "has-config", hint = "Run `mytool init`")]
+/// fn config_exists() -> Result<(CheckResult, String), Box<dyn std::error::Error + Send + Sync>> {
+///     Ok((CheckResult::Ok, "Config file found".to_string()))
+/// }
+///
+/// let checks = medic::registered_checks();
+/// assert!(checks.iter().any(|check| check.name() == "has-config"));
+/// ```
+///
+/// Properties:
+/// * `name`: Name of check (defaults to the name of the function, with `_`
+///   replaced by `-`)
+/// * `hint`: Hint shown on warnings and errors (see [`Check::with_hint`])
+#[cfg(feature = "macros")]
+pub use medic_macros::check;
+pub use redact::RedactionRules;
+#[cfg(feature = "macros")]
+pub use registry::registered_checks;
+#[cfg(feature = "serde")]
+pub use render::render_json;
+pub use render::render_markdown;
+pub use render::render_markdown_details;
+pub use render::render_table;
+pub use render::render_with;
+pub use render::MarkdownRenderer;
+pub use render::OutputFormat;
+pub use render::RowRenderer;
+pub use render::TableLayout;
+pub use render::TableRenderer;
+pub use report::MedicReport;
+pub use run::run_checks;
+pub use run::run_checks_parallel;
+pub use run::run_checks_with;
+pub use run::run_checks_with_progress;
+pub use run::worst_actionable;
+pub use run::worst_result;
+pub use run::CheckProgress;
+pub use run::MedicOptions;
 use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 use strum::IntoStaticStr;
+pub use summary::summary;
+pub use summary::summary_markdown;
+pub use summary::summary_markdown_with_config;
+pub use summary::summary_with_config;
+pub use summary::summary_with_threshold;
+pub use summary::SummaryConfig;
 use thiserror::Error;
 
+#[cfg(feature = "async")]
+mod async_checks;
 pub mod checks;
+mod diff;
+mod exit;
+mod fix;
+mod group;
+#[cfg(any(feature = "log", feature = "tracing"))]
+mod logging;
+mod redact;
+#[cfg(feature = "macros")]
+mod registry;
+mod render;
+mod report;
+mod run;
+mod summary;
 #[cfg(test)]
 mod tests;
 
+// Allow the code generated by the macros to refer to `::medic` in the tests
+#[cfg(all(test, feature = "macros"))]
+extern crate self as medic;
+
+/// Not public API, used by the code generated by the macros.
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::registry::RegisteredCheck;
+    pub use inventory;
+}
+
 /// Error from medic
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -48,91 +218,419 @@ pub enum MedicError {
     IoError(#[from] std::io::Error),
     #[error("Error from check")]
     CheckError(#[from] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Expected checks did not run: {}", .0.join(", "))]
+    MissingChecks(Vec<String>),
+    #[error("Multiple checks with the same name: {}", .0.join(", "))]
+    DuplicateChecks(Vec<String>),
+    #[cfg(feature = "serde")]
+    #[error("Invalid saved report")]
+    InvalidReport(#[from] serde_json::Error),
 }
 
 /// Perform environment sanity check
 ///
+/// The output contains colour codes, see [Colours](crate#colours).
+///
 /// Returns the worst level found (which can be passed to [`summary`])
 pub fn medic<'iter>(
     output: &mut impl Write,
     checks: impl Iterator<Item = &'iter Check>,
 ) -> Result<CheckResult, MedicError> {
-    let mut worst_issues_found = CheckResult::Ok;
+    medic_with_renderer(output, checks, &TableRenderer::new())
+}
+
+/// Perform environment sanity check, only showing results at or above
+/// `min_level`
+///
+/// The worst level is still computed across *all* checks, so a hidden result
+/// still affects the [`summary`]. Column widths are based only on the rows
+/// shown. Passing [`CheckResult::Warning`] gives a terse mode, while
+/// [`CheckResult::Ok`] is equivalent to [`medic`] (except that skipped checks
+/// are hidden).
+///
+/// Returns the worst level found (which can be passed to [`summary`])
+pub fn medic_filtered<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter Check>,
+    min_level: CheckResult,
+) -> Result<CheckResult, MedicError> {
+    medic_with_options(output, checks, &MedicOptions::new().min_level(min_level))
+}
+
+/// Perform environment sanity check, running all checks concurrently
+///
+/// Each check runs on its own thread, which helps when checks block on I/O
+/// (such as running external programs). The results are still shown in the
+/// original order, and are otherwise identical to [`medic`]. To combine this
+/// with other options, use [`medic_with_options`] with
+/// [`MedicOptions::parallel`].
+///
+/// Returns the worst level found (which can be passed to [`summary`])
+pub fn medic_parallel<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter Check>,
+) -> Result<CheckResult, MedicError> {
+    let outcomes = run_checks_parallel(checks);
+    render_table(output, &outcomes)?;
+    Ok(worst_result(&outcomes))
+}
+
+/// Perform environment sanity check, with options controlling how checks are
+/// run
+///
+/// See [`MedicOptions`] for what can be controlled.
+///
+/// Returns the worst level found (which can be passed to [`summary`])
+pub fn medic_with_options<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter Check>,
+    options: &MedicOptions,
+) -> Result<CheckResult, MedicError> {
+    let outcomes = run_checks_with(checks, options);
+    if let Some(shown) = options.visible(&outcomes) {
+        render_with(output, &shown, &options.table_renderer())?;
+    }
+    Ok(worst_result(&outcomes))
+}
+
+/// Perform environment sanity check, using a custom renderer
+///
+/// This separates running the checks (and computing the [`TableLayout`]) from
+/// the presentation, which is fully up to the [`RowRenderer`].
+///
+/// Returns the worst level found (which can be passed to [`summary`])
+pub fn medic_with_renderer<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter Check>,
+    renderer: &impl RowRenderer,
+) -> Result<CheckResult, MedicError> {
     // Buffer output messages so that we can format them in a nice table
-    let mut results = vec![];
-
-    for Check { name, func } in checks {
-        match func() {
-            Ok((result, text)) => {
-                results.push((result, *name, text));
-                if result >= worst_issues_found {
-                    worst_issues_found = result;
-                }
-            }
-            Err(err) => {
-                results.push((CheckResult::Fatal, *name, format!("{err}")));
-                worst_issues_found = CheckResult::Fatal;
-            }
+    let outcomes = run_checks(checks);
+    render_with(output, &outcomes, renderer)?;
+    Ok(worst_result(&outcomes))
+}
+
+/// Perform environment sanity check, writing the results as JSON
+///
+/// The output is an object with the worst level found (`worst`) and an array
+/// of the results (`checks`), each with the fields `name`, `result` and
+/// `message`. There may also be `hint` (see [`Check::with_hint`]), `details`
+/// as an object (see [`Check::new_detailed`]) and `extended` (see
+/// [`DetailedResult::extended`]).
+/// The result levels are lower case strings (e.g. `"warning"`).
+/// Messages are included verbatim (without any alignment) and no colour codes
+/// are emitted. The format is stable, so the output can be saved and later
+/// compared with a new run (see [`SavedReport`] and [`diff_reports`]).
+///
+/// ```json
+/// {
+///   "worst": "warning",
+///   "checks": [
+///     { "name": "version", "result": "ok", "message": "3.1.2" },
+///     { "name": "build", "result": "warning", "message": "Github CI build" }
+///   ]
+/// }
+/// ```
+///
+/// Returns the worst level found (which can be passed to [`summary`])
+#[cfg(feature = "serde")]
+pub fn medic_json<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter Check>,
+) -> Result<CheckResult, MedicError> {
+    let outcomes = run_checks(checks);
+    let worst = worst_result(&outcomes);
+    render_json(output, &outcomes)?;
+    Ok(worst)
+}
+
+/// Perform environment sanity check, writing the results as a Markdown table
+///
+/// This produces a GitHub flavoured Markdown table, suitable for pasting
+/// directly into an issue. `|` characters in messages are escaped and line
+/// breaks are replaced with `<br>`. No colour codes are emitted. Use
+/// [`summary_markdown`] for the summary line.
+///
+/// To wrap the table in a collapsible block, use [`run_checks`] together with
+/// [`render_markdown_details`] instead.
+///
+/// Returns the worst level found (which can be passed to [`summary_markdown`])
+pub fn medic_markdown<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter Check>,
+) -> Result<CheckResult, MedicError> {
+    medic_with_renderer(output, checks, &MarkdownRenderer)
+}
+
+/// Perform environment sanity check, writing to an [`anstream`] stream
+///
+/// This is the recommended way to write to a terminal, as the stream takes
+/// care of stripping colour codes when the destination doesn't support them
+/// (e.g. when output is redirected to a file):
+///
+/// ```no_run
+/// let mut stdout = anstream::stdout();
+/// let worst = medic::medic_stream(&mut stdout, [medic::checks::CHECK_HOST].iter())?;
+/// medic::summary(&mut stdout, worst)?;
+/// # Ok::<(), medic::MedicError>(())
+/// ```
+///
+/// Returns the worst level found (which can be passed to [`summary`])
+pub fn medic_stream<'iter, W: anstream::stream::RawStream + anstream::stream::AsLockedWrite>(
+    stream: &mut anstream::AutoStream<W>,
+    checks: impl Iterator<Item = &'iter Check>,
+) -> Result<CheckResult, MedicError> {
+    medic(stream, checks)
+}
+
+/// Perform environment sanity check, showing each row as soon as it is done
+///
+/// Normally all checks are run before anything is shown, so with slow checks
+/// (e.g. running external programs) the output can appear frozen. Here the
+/// column widths are instead fixed up front based on the names of the
+/// checks, and each row is written (and flushed) as soon as it and all rows
+/// before it are done. Combined with [`MedicOptions::parallel`] this gives
+/// incremental output while the checks run concurrently.
+///
+/// Apart from the width (see [`MedicOptions::width`]), the options only
+/// affect how the checks are run: rows are never hidden and there is no TIME
+/// column. For other progress reporting (such as a spinner)
+/// see [`run_checks_with_progress`]. The output contains colour codes, see
+/// [Colours](crate#colours).
+///
+/// Returns the worst level found (which can be passed to [`summary`])
+pub fn medic_incremental<'iter>(
+    output: &mut (impl Write + Send),
+    checks: impl Iterator<Item = &'iter Check>,
+    options: &MedicOptions,
+) -> Result<CheckResult, MedicError> {
+    let checks: Vec<_> = checks.collect();
+    let table = render::IncrementalTable::new(
+        output,
+        options.table_renderer(),
+        TableLayout::for_checks(&checks),
+    )?;
+    let outcomes = run_checks_with_progress(checks.into_iter(), options, &table);
+    table.finish()?;
+    Ok(worst_result(&outcomes))
+}
+
+/// Perform environment sanity check with an overall deadline
+///
+/// Checks are run in order (after any checks they depend on, see
+/// [`Check::depends_on`]) until `total` has elapsed. Any remaining checks are
+/// not run, and are instead reported as [`CheckResult::Skipped`]. A check
+/// that has already started when the deadline passes is allowed to finish,
+/// so combine this with [`MedicOptions::check_timeout`] if a check could
+/// hang. Otherwise this works like [`medic_with_options`].
+///
+/// Returns the worst level found (which can be passed to [`summary`])
+pub fn medic_deadline<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter Check>,
+    total: Duration,
+    options: &MedicOptions,
+) -> Result<CheckResult, MedicError> {
+    let options = options.clone().deadline(Instant::now().checked_add(total));
+    medic_with_options(output, checks, &options)
+}
+
+/// Perform environment sanity check, requiring a specific set of checks
+///
+/// This works like [`medic`], but after running the checks it verifies that
+/// every name in `expected_names` was among the checks executed. This is
+/// useful in CI to catch a check that was accidentally compiled out (e.g.
+/// behind a feature flag). Checks reported as [`CheckResult::Skipped`] (such
+/// as because a dependency failed) count as not executed.
+///
+/// Returns [`MedicError::MissingChecks`] listing the absent names if any
+/// expected check did not run.
+pub fn medic_require<'iter>(
+    output: &mut impl Write,
+    checks: impl Iterator<Item = &'iter Check>,
+    expected_names: &[&str],
+) -> Result<CheckResult, MedicError> {
+    let outcomes = run_checks(checks);
+    render_table(output, &outcomes)?;
+
+    let missing: Vec<String> = expected_names
+        .iter()
+        .filter(|expected| {
+            !outcomes
+                .iter()
+                .any(|outcome| outcome.name == **expected && outcome.result != CheckResult::Skipped)
+        })
+        .map(|expected| (*expected).to_owned())
+        .collect();
+    if !missing.is_empty() {
+        return Err(MedicError::MissingChecks(missing));
+    }
+    Ok(worst_result(&outcomes))
+}
+
+/// Short, coloured health hint suitable for embedding in other output
+///
+/// This is intended to be appended to e.g. the `--version` output, and
+/// produces strings such as `[ok]`, `[2 warnings]` or `[1 error, 2 warnings]`.
+/// The colour is based on `worst`.
+pub fn health_hint(worst: CheckResult, counts: &ResultCounts) -> String {
+    let parts: Vec<String> = [
+        (counts.fatal, "fatal", "fatal"),
+        (counts.error, "error", "errors"),
+        (counts.warning, "warning", "warnings"),
+    ]
+    .into_iter()
+    .filter(|(count, _, _)| *count > 0)
+    .map(|(count, singular, plural)| {
+        format!("{count} {}", if count == 1 { singular } else { plural })
+    })
+    .collect();
+    let text = if parts.is_empty() {
+        "ok".to_owned()
+    } else {
+        parts.join(", ")
+    };
+    let style = worst.style();
+    format!("{}[{text}]{}", style.render(), style.render_reset())
+}
+
+/// Compute a numeric health score (0-100) using the default [`HealthWeights`]
+///
+/// Unlike the worst result, this takes the mix of all results into account,
+/// which makes it suitable for tracking trends across runs (e.g. on a
+/// dashboard).
+pub fn health_score(results: impl IntoIterator<Item = CheckResult>) -> u8 {
+    HealthWeights::default().score(&results.into_iter().collect())
+}
+
+/// Weighting used to compute a health score
+///
+/// Each check contributes a number of points (0-100) depending on its result,
+/// and the score is the average over all checks. Skipped checks are not
+/// counted, and a run without any (non-skipped) checks scores 100.
+///
+/// The default weights are stable and are:
+///
+/// | Result  | Points |
+/// |---------|--------|
+/// | Ok      | 100    |
+/// | Info    | 100    |
+/// | Warning | 60     |
+/// | Error   | 20     |
+/// | Fatal   | 0      |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthWeights {
+    /// Points for [`CheckResult::Ok`]
+    pub ok: u8,
+    /// Points for [`CheckResult::Info`]
+    pub info: u8,
+    /// Points for [`CheckResult::Warning`]
+    pub warning: u8,
+    /// Points for [`CheckResult::Error`]
+    pub error: u8,
+    /// Points for [`CheckResult::Fatal`]
+    pub fatal: u8,
+}
+
+impl Default for HealthWeights {
+    fn default() -> Self {
+        Self {
+            ok: 100,
+            info: 100,
+            warning: 60,
+            error: 20,
+            fatal: 0,
         }
     }
-    let mut status_width = "RESULT".len();
-    let mut name_width = "CHECK".len();
-    for (status, name, _) in &results {
-        status_width = max(
-            status_width,
-            <&CheckResult as Into<&str>>::into(status).len(),
-        );
-        name_width = max(name_width, name.len());
+}
+
+impl HealthWeights {
+    /// Compute the health score (0-100) for the given result counts
+    ///
+    /// Weights above 100 are treated as 100.
+    pub fn score(&self, counts: &ResultCounts) -> u8 {
+        let weighted = [
+            (counts.ok, self.ok),
+            (counts.info, self.info),
+            (counts.warning, self.warning),
+            (counts.error, self.error),
+            (counts.fatal, self.fatal),
+        ];
+        let total: u64 = weighted.iter().map(|(count, _)| *count as u64).sum();
+        if total == 0 {
+            return 100;
+        }
+        let points: u64 = weighted
+            .iter()
+            .map(|(count, weight)| *count as u64 * u64::from((*weight).min(100)))
+            .sum();
+        // Round to nearest, the result is at most 100 so it fits in a u8
+        ((points + total / 2) / total) as u8
     }
+}
 
-    let text_alignment = status_width + name_width + 4;
+/// Number of checks that resulted in each severity level
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResultCounts {
+    /// Number of [`CheckResult::Skipped`]
+    pub skipped: usize,
+    /// Number of [`CheckResult::Ok`]
+    pub ok: usize,
+    /// Number of [`CheckResult::Info`]
+    pub info: usize,
+    /// Number of [`CheckResult::Warning`]
+    pub warning: usize,
+    /// Number of [`CheckResult::Error`]
+    pub error: usize,
+    /// Number of [`CheckResult::Fatal`]
+    pub fatal: usize,
+}
 
-    writeln!(
-        output,
-        "{}{: <status_width$}  {: <name_width$}  MESSAGE{}",
-        Effects::BOLD.render(),
-        "RESULT",
-        "CHECK",
-        Reset.render()
-    )?;
-    for (status, name, text) in results {
-        let text = text.replace(
-            '\n',
-            &("\n".to_owned() + " ".repeat(text_alignment).as_str()),
-        );
-        writeln!(
-            output,
-            "{status: <status_width$}  {name: <name_width$}  {text}"
-        )?;
-    }
-
-    Ok(worst_issues_found)
-}
-
-/// Print summary line at the end
-pub fn summary(output: &mut impl Write, worst_issues_found: CheckResult) -> Result<(), MedicError> {
-    if worst_issues_found >= CheckResult::Error {
-        writeln!(
-            output,
-            "\n{}Error{}: Error(s) found, you should rectify these for proper operation",
-            AnsiColor::Red.render_fg(),
-            Reset.render()
-        )?;
-    } else if worst_issues_found >= CheckResult::Warning {
-        writeln!(
-            output,
-            "\n{}Warning{}: Warning(s) found, consider investigating (especially if you have \
-             issues)",
-            AnsiColor::Yellow.render_fg(),
-            Reset.render()
-        )?;
-    }
-    Ok(())
+impl ResultCounts {
+    /// Count one more result
+    pub fn add(&mut self, result: CheckResult) {
+        match result {
+            CheckResult::Skipped => self.skipped += 1,
+            CheckResult::Ok => self.ok += 1,
+            CheckResult::Info => self.info += 1,
+            CheckResult::Warning => self.warning += 1,
+            CheckResult::Error => self.error += 1,
+            CheckResult::Fatal => self.fatal += 1,
+        }
+    }
+}
+
+impl Extend<CheckResult> for ResultCounts {
+    fn extend<T: IntoIterator<Item = CheckResult>>(&mut self, iter: T) {
+        for result in iter {
+            self.add(result);
+        }
+    }
+}
+
+impl FromIterator<CheckResult> for ResultCounts {
+    fn from_iter<T: IntoIterator<Item = CheckResult>>(iter: T) -> Self {
+        let mut counts = Self::default();
+        counts.extend(iter);
+        counts
+    }
 }
 
 /// Result of a check (the level of severity)
+///
+/// The ordering is by severity, with [`CheckResult::Info`] above
+/// [`CheckResult::Ok`]. See [`worst_result`] and [`worst_actionable`] for the
+/// difference this makes when aggregating results.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, IntoStaticStr)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum CheckResult {
+    /// The check was not run (e.g. because a deadline was exceeded or a
+    /// dependency failed)
+    Skipped,
     /// This notes a value that is within expected parameters
     Ok,
     /// Information, not a problem in itself (but might be interesting for
@@ -142,7 +640,8 @@ pub enum CheckResult {
     Warning,
     /// Error, definitely a problem
     Error,
-    /// Fatal error, the check itself couldn't complete (returned an [`Err`])
+    /// Fatal error, the check itself couldn't complete (returned an [`Err`] or
+    /// timed out, see also [`Check::on_error`])
     Fatal,
 }
 
@@ -150,6 +649,7 @@ impl CheckResult {
     /// Get style for this severity level
     const fn style(&self) -> anstyle::Style {
         match self {
+            Self::Skipped => anstyle::Style::new().effects(Effects::DIMMED),
             Self::Ok => AnsiColor::Green.on_default(),
             Self::Info => AnsiColor::Green.on_default(),
             Self::Warning => AnsiColor::Yellow.on_default(),
@@ -178,6 +678,174 @@ impl std::fmt::Display for CheckResult {
     }
 }
 
+/// Outcome of running a check
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CheckOutcome {
+    /// Name of the check
+    pub name: &'static str,
+    /// Severity level
+    pub result: CheckResult,
+    /// Message describing the situation (may be multi-line)
+    pub message: String,
+    /// True if the check function returned an error or timed out (the message
+    /// is then the error and the result is [`CheckResult::Fatal`], unless
+    /// changed with [`Check::on_error`])
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub errored: bool,
+    /// How long the check took to run (only measured if enabled with
+    /// [`MedicOptions::timing`])
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub duration: Option<Duration>,
+    /// Suggested fix (only present if the result is [`CheckResult::Warning`]
+    /// or worse, see [`Check::with_hint`] and [`DetailedResult::hint`])
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub hint: Option<String>,
+    /// Structured details as key/value pairs, in order (see
+    /// [`Check::new_detailed`])
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Vec::is_empty",
+            serialize_with = "serialize_details"
+        )
+    )]
+    pub details: Vec<(String, String)>,
+    /// Extended output, only collected with verbose output (see
+    /// [`DetailedResult::extended`])
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub extended: Option<String>,
+}
+
+/// Serialize details as a JSON object (preserving the order)
+#[cfg(feature = "serde")]
+fn serialize_details<S: serde::Serializer>(
+    details: &[(String, String)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(details.iter().map(|(key, value)| (key, value)))
+}
+
+impl CheckOutcome {
+    /// Get the error returned by the check function, if any
+    pub fn error(&self) -> Option<&str> {
+        self.errored.then_some(self.message.as_str())
+    }
+}
+
+/// Result of a check with structured details, see [`Check::new_detailed`]
+pub struct DetailedResult {
+    /// Severity level
+    pub result: CheckResult,
+    /// Message describing the situation (may be multi-line)
+    pub message: String,
+    /// Details as key/value pairs (such as `version` or `path`), in order
+    pub details: Vec<(String, String)>,
+    /// Suggested fix, overriding the one from [`Check::with_hint`]
+    pub hint: Option<String>,
+    /// Function producing extended output, see [`DetailedResult::extended`]
+    extended: Option<ExtendedFn>,
+}
+
+impl DetailedResult {
+    /// Create a result without any details
+    pub fn new(result: CheckResult, message: impl Into<String>) -> Self {
+        Self {
+            result,
+            message: message.into(),
+            details: vec![],
+            hint: None,
+            extended: None,
+        }
+    }
+
+    /// Add a detail
+    pub fn detail(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.details.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set a suggested fix based on what the check found
+    ///
+    /// This takes precedence over the hint set with [`Check::with_hint`], and
+    /// is likewise only shown when the result is [`CheckResult::Warning`] or
+    /// worse.
+    ///
+    /// ```
+    /// use medic::Check;
+    /// use medic::CheckResult;
+    /// use medic::DetailedResult;
+    ///
+    /// let check = Check::new_detailed("config", || {
+    ///     let path = std::env::temp_dir().join("mytool.toml");
+    ///     Ok(DetailedResult::new(CheckResult::Error, "Config file missing")
+    ///         .hint(format!("Run `mytool init` to create {}", path.display())))
+    /// });
+    /// ```
+    pub fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Attach a function producing extended output (such as full command
+    /// output or a dump of the configuration)
+    ///
+    /// This is only called when verbose output is enabled (see
+    /// [`MedicOptions::verbose`]), right after the check function returns, so
+    /// it can use what the check found and is covered by
+    /// [`MedicOptions::check_timeout`]. The output is stored in
+    /// [`CheckOutcome::extended`] and shown below the message, so that the
+    /// default output can stay compact.
+    ///
+    /// ```
+    /// use medic::Check;
+    /// use medic::CheckResult;
+    /// use medic::DetailedResult;
+    ///
+    /// let check = Check::new_detailed("config", || {
+    ///     let path = std::env::temp_dir().join("mytool.toml");
+    ///     Ok(DetailedResult::new(CheckResult::Ok, "Config is valid")
+    ///         .extended(move || Ok(std::fs::read_to_string(path)?)))
+    /// });
+    /// ```
+    pub fn extended(
+        mut self,
+        extended: impl FnOnce() -> Result<String, Box<dyn std::error::Error + Send + Sync>>
+            + Send
+            + 'static,
+    ) -> Self {
+        self.extended = Some(Box::new(extended));
+        self
+    }
+
+    /// Split off the extended output, calling the function only if `verbose`
+    fn take_extended(&mut self, verbose: bool) -> Option<String> {
+        let extended = self.extended.take().filter(|_| verbose)?;
+        Some(match extended() {
+            Ok(extended) => extended,
+            Err(err) => format!("Failed to get extended output: {err}"),
+        })
+    }
+}
+
+impl std::fmt::Debug for DetailedResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DetailedResult")
+            .field("result", &self.result)
+            .field("message", &self.message)
+            .field("details", &self.details)
+            .field("hint", &self.hint)
+            .field("has_extended", &self.extended.is_some())
+            .finish()
+    }
+}
+
+impl From<(CheckResult, String)> for DetailedResult {
+    fn from((result, message): (CheckResult, String)) -> Self {
+        Self::new(result, message)
+    }
+}
+
 /// Type of function that performs a check
 ///
 /// This should return the severity level and a message describing the situation
@@ -185,19 +853,243 @@ impl std::fmt::Display for CheckResult {
 /// Multi-line messages are supported, the framework handles alignment.
 pub type CheckFn = fn() -> Result<(CheckResult, String), Box<dyn std::error::Error + Send + Sync>>;
 
+/// Shared check function that may capture state
+///
+/// This is reference counted so that it can be moved to a separate thread
+/// (see [`MedicOptions::check_timeout`]).
+type SharedCheckFn = Arc<
+    dyn Fn() -> Result<(CheckResult, String), Box<dyn std::error::Error + Send + Sync>>
+        + Send
+        + Sync,
+>;
+
+/// Function producing extended output for a check, see
+/// [`DetailedResult::extended`]
+type ExtendedFn =
+    Box<dyn FnOnce() -> Result<String, Box<dyn std::error::Error + Send + Sync>> + Send>;
+
+/// Shared check function returning structured details
+type SharedDetailedFn =
+    Arc<dyn Fn() -> Result<DetailedResult, Box<dyn std::error::Error + Send + Sync>> + Send + Sync>;
+
+/// The different kinds of functions a check can be backed by
+#[derive(Clone)]
+enum CheckFunc {
+    /// Plain function pointer (usable in `const` context)
+    Fn(CheckFn),
+    /// Closure that may capture state
+    Shared(SharedCheckFn),
+    /// Closure returning structured details
+    Detailed(SharedDetailedFn),
+}
+
+impl CheckFunc {
+    /// Call the check function
+    fn call(&self) -> Result<DetailedResult, Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            Self::Fn(func) => func().map(Into::into),
+            Self::Shared(func) => func().map(Into::into),
+            Self::Detailed(func) => func(),
+        }
+    }
+}
+
 /// A check with a name
-#[derive(Debug)]
 pub struct Check {
     name: &'static str,
-    func: CheckFn,
+    func: CheckFunc,
+    hint: Option<&'static str>,
+    fix: Option<fix::FixFn>,
+    error_result: CheckResult,
+    dependencies: &'static [&'static str],
 }
 
 impl Check {
-    /// Create a new check
+    /// Create a new check from a function or closure
+    ///
+    /// Unlike [`Check::new_const`] the closure may capture state, such as
+    /// configuration or paths discovered at runtime:
+    ///
+    /// ```
+    /// use medic::Check;
+    /// use medic::CheckResult;
+    ///
+    /// let dir = std::env::temp_dir();
+    /// let check = Check::new("config-dir", move || {
+    ///     Ok((CheckResult::Ok, format!("{}", dir.display())))
+    /// });
+    /// assert_eq!(check.name(), "config-dir");
+    /// ```
+    ///
+    /// As checks may be run from multiple threads (see
+    /// [`run_checks_parallel`]) mutable state needs to be behind a
+    /// [`Mutex`](std::sync::Mutex) or similar.
+    ///
+    /// * `name`: Name of check (for display)
+    /// * `func`: Function to perform the check
+    pub fn new(
+        name: &'static str,
+        func: impl Fn() -> Result<(CheckResult, String), Box<dyn std::error::Error + Send + Sync>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            name,
+            func: CheckFunc::Shared(Arc::new(func)),
+            hint: None,
+            fix: None,
+            error_result: CheckResult::Fatal,
+            dependencies: &[],
+        }
+    }
+
+    /// Create a new check that reports structured details in addition to the
+    /// message
+    ///
+    /// The details are shown in the table as aligned lines below the message,
+    /// and are included in structured output (such as JSON).
+    ///
+    /// ```
+    /// use medic::Check;
+    /// use medic::CheckResult;
+    /// use medic::DetailedResult;
+    ///
+    /// let check = Check::new_detailed("has-chezmoi", || {
+    ///     Ok(DetailedResult::new(CheckResult::Ok, "Chezmoi found")
+    ///         .detail("path", "/usr/bin/chezmoi")
+    ///         .detail("version", "2.46.1"))
+    /// });
+    /// ```
+    ///
+    /// * `name`: Name of check (for display)
+    /// * `func`: Function to perform the check
+    pub fn new_detailed(
+        name: &'static str,
+        func: impl Fn() -> Result<DetailedResult, Box<dyn std::error::Error + Send + Sync>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            name,
+            func: CheckFunc::Detailed(Arc::new(func)),
+            hint: None,
+            fix: None,
+            error_result: CheckResult::Fatal,
+            dependencies: &[],
+        }
+    }
+
+    /// Create a new check in `const` context
     ///
     /// * `name`: Name of check (for display)
     /// * `func`: Function to perform the check
-    pub const fn new(name: &'static str, func: CheckFn) -> Self {
-        Self { name, func }
+    pub const fn new_const(name: &'static str, func: CheckFn) -> Self {
+        Self {
+            name,
+            func: CheckFunc::Fn(func),
+            hint: None,
+            fix: None,
+            error_result: CheckResult::Fatal,
+            dependencies: &[],
+        }
+    }
+
+    /// Only run this check if the checks with the given names succeed
+    ///
+    /// The dependencies are run first, and if any of them doesn't report
+    /// [`CheckResult::Ok`] or [`CheckResult::Info`] this check is reported as
+    /// [`CheckResult::Skipped`] with the reason, instead of producing
+    /// misleading errors.
+    ///
+    /// ```
+    /// use medic::checks;
+    /// use medic::Check;
+    /// use medic::CheckResult;
+    ///
+    /// let checks = [
+    ///     checks::check_in_path("chezmoi"),
+    ///     Check::new("chezmoi-config", || {
+    ///         // Run `chezmoi` to check the config here
+    ///         Ok((CheckResult::Ok, "Config is valid".to_string()))
+    ///     })
+    ///     .depends_on(&["chezmoi"]),
+    /// ];
+    /// ```
+    pub const fn depends_on(mut self, names: &'static [&'static str]) -> Self {
+        self.dependencies = names;
+        self
+    }
+
+    /// Attach a suggested fix, shown when the check finds a problem
+    ///
+    /// The hint is included in the outcome (see [`CheckOutcome::hint`]) when
+    /// the result is [`CheckResult::Warning`] or worse, and is rendered on a
+    /// separate line below the message. This is the default, a check created
+    /// with [`Check::new_detailed`] can return a hint based on what it found
+    /// (see [`DetailedResult::hint`]).
+    ///
+    /// ```
+    /// use medic::Check;
+    /// use medic::CheckResult;
+    ///
+    /// const CHECK_CONFIG: Check = Check::new_const("config", || {
+    ///     Ok((CheckResult::Error, "Config file missing".to_string()))
+    /// })
+    /// .with_hint("Run `mytool init` to create it");
+    /// ```
+    pub const fn with_hint(mut self, hint: &'static str) -> Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    /// Set the result used if the check function returns an error (the
+    /// default is [`CheckResult::Fatal`])
+    ///
+    /// This is useful for optional probes where an error is expected, such as
+    /// querying a package manager that might not be installed.
+    pub const fn on_error(mut self, result: CheckResult) -> Self {
+        self.error_result = result;
+        self
+    }
+
+    /// Attach a function that can fix the problem found by the check
+    ///
+    /// The function should return a description of what it did. Fixes are
+    /// only applied by [`medic_fix`].
+    pub fn with_fix(
+        mut self,
+        fix: impl Fn() -> Result<String, Box<dyn std::error::Error + Send + Sync>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.fix = Some(Box::new(fix));
+        self
+    }
+
+    /// Get the name of the check
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Run the check function (ignoring any details)
+    #[cfg(test)]
+    fn run(&self) -> Result<(CheckResult, String), Box<dyn std::error::Error + Send + Sync>> {
+        self.func
+            .call()
+            .map(|detailed| (detailed.result, detailed.message))
+    }
+}
+
+impl std::fmt::Debug for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Check")
+            .field("name", &self.name)
+            .field("hint", &self.hint)
+            .field("has_fix", &self.fix.is_some())
+            .field("dependencies", &self.dependencies)
+            .finish_non_exhaustive()
     }
 }
@@ -0,0 +1,144 @@
+//! Reporting of check results through the `log` and `tracing` crates
+
+use crate::run_checks_with;
+use crate::worst_result;
+use crate::Check;
+use crate::CheckOutcome;
+use crate::CheckResult;
+use crate::MedicOptions;
+
+/// Map a result to a log level
+///
+/// | Result         | Level   |
+/// |----------------|---------|
+/// | Skipped        | Debug   |
+/// | Ok, Info       | Info    |
+/// | Warning        | Warn    |
+/// | Error, Fatal   | Error   |
+#[cfg(feature = "log")]
+impl From<CheckResult> for log::Level {
+    fn from(result: CheckResult) -> Self {
+        match result {
+            CheckResult::Skipped => Self::Debug,
+            CheckResult::Ok | CheckResult::Info => Self::Info,
+            CheckResult::Warning => Self::Warn,
+            CheckResult::Error | CheckResult::Fatal => Self::Error,
+        }
+    }
+}
+
+/// Map a result to a tracing level (the same mapping as for `log`)
+#[cfg(feature = "tracing")]
+impl From<CheckResult> for tracing::Level {
+    fn from(result: CheckResult) -> Self {
+        match result {
+            CheckResult::Skipped => Self::DEBUG,
+            CheckResult::Ok | CheckResult::Info => Self::INFO,
+            CheckResult::Warning => Self::WARN,
+            CheckResult::Error | CheckResult::Fatal => Self::ERROR,
+        }
+    }
+}
+
+/// Emit each outcome as a log record (with the target `medic`)
+///
+/// The level is based on the result (see the conversion to [`log::Level`]),
+/// and the record has the form `name: Result: message`, followed by the hint
+/// if there is one.
+#[cfg(feature = "log")]
+pub fn log_outcomes(outcomes: &[CheckOutcome]) {
+    for outcome in outcomes {
+        let result: &'static str = outcome.result.into();
+        let level = log::Level::from(outcome.result);
+        match &outcome.hint {
+            Some(hint) => log::log!(
+                target: "medic",
+                level,
+                "{}: {result}: {} (hint: {hint})",
+                outcome.name,
+                outcome.message
+            ),
+            None => log::log!(
+                target: "medic",
+                level,
+                "{}: {result}: {}",
+                outcome.name,
+                outcome.message
+            ),
+        }
+    }
+}
+
+/// Perform environment sanity check, logging the results with `log`
+///
+/// This is intended for headless use (such as startup checks of a daemon),
+/// see [`log_outcomes`]. The options control how checks are run, and which
+/// outcomes are logged (see [`MedicOptions::min_level`] and
+/// [`MedicOptions::quiet`]). Use `&MedicOptions::new()` to log everything.
+///
+/// Returns the worst level found
+#[cfg(feature = "log")]
+pub fn medic_log<'iter>(
+    checks: impl Iterator<Item = &'iter Check>,
+    options: &MedicOptions,
+) -> CheckResult {
+    let outcomes = run_checks_with(checks, options);
+    if let Some(shown) = options.visible(&outcomes) {
+        log_outcomes(&shown);
+    }
+    worst_result(&outcomes)
+}
+
+/// Emit each outcome as a tracing event (with the target `medic`)
+///
+/// The level is based on the result (see the conversion to
+/// [`tracing::Level`]). The event has the fields `check`, `result` and `hint`
+/// (if there is one), with the message of the check as the message.
+#[cfg(feature = "tracing")]
+pub fn trace_outcomes(outcomes: &[CheckOutcome]) {
+    // The level of an event has to be a constant
+    macro_rules! event {
+        ($level:expr, $outcome:expr, $result:expr) => {
+            tracing::event!(
+                target: "medic",
+                $level,
+                check = $outcome.name,
+                result = $result,
+                hint = $outcome.hint.as_deref(),
+                "{}",
+                $outcome.message
+            )
+        };
+    }
+
+    for outcome in outcomes {
+        let result: &'static str = outcome.result.into();
+        match tracing::Level::from(outcome.result) {
+            tracing::Level::ERROR => event!(tracing::Level::ERROR, outcome, result),
+            tracing::Level::WARN => event!(tracing::Level::WARN, outcome, result),
+            tracing::Level::INFO => event!(tracing::Level::INFO, outcome, result),
+            tracing::Level::DEBUG => event!(tracing::Level::DEBUG, outcome, result),
+            _ => event!(tracing::Level::TRACE, outcome, result),
+        }
+    }
+}
+
+/// Perform environment sanity check, emitting the results as `tracing`
+/// events
+///
+/// This is intended for headless use (such as startup checks of a daemon),
+/// see [`trace_outcomes`]. The options are used the same way as for
+/// [`medic_log`].
+///
+/// Returns the worst level found
+#[cfg(feature = "tracing")]
+pub fn medic_trace<'iter>(
+    checks: impl Iterator<Item = &'iter Check>,
+    options: &MedicOptions,
+) -> CheckResult {
+    let outcomes = run_checks_with(checks, options);
+    if let Some(shown) = options.visible(&outcomes) {
+        trace_outcomes(&shown);
+    }
+    worst_result(&outcomes)
+}
@@ -0,0 +1,194 @@
+//! Redaction of sensitive data in check messages
+
+use crate::CheckOutcome;
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+/// Rules for removing sensitive data from check messages
+///
+/// Doctor output often ends up in public issues, so by default the home
+/// directory is replaced with `~` and the user name with `<user>`. Further
+/// patterns can be added with `RedactionRules::pattern` (this requires the
+/// `regex` feature).
+///
+/// Use with [`MedicOptions::redact`](crate::MedicOptions::redact), or apply to
+/// outcomes directly with [`RedactionRules::apply_to`].
+#[derive(Debug, Clone)]
+pub struct RedactionRules {
+    home: bool,
+    username: bool,
+    #[cfg(feature = "regex")]
+    patterns: Vec<(Regex, String)>,
+}
+
+impl Default for RedactionRules {
+    fn default() -> Self {
+        Self {
+            home: true,
+            username: true,
+            #[cfg(feature = "regex")]
+            patterns: vec![],
+        }
+    }
+}
+
+impl PartialEq for RedactionRules {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "regex")]
+        let patterns_eq = self.patterns.len() == other.patterns.len()
+            && self
+                .patterns
+                .iter()
+                .zip(&other.patterns)
+                .all(|((re1, rep1), (re2, rep2))| re1.as_str() == re2.as_str() && rep1 == rep2);
+        #[cfg(not(feature = "regex"))]
+        let patterns_eq = true;
+        self.home == other.home && self.username == other.username && patterns_eq
+    }
+}
+
+impl Eq for RedactionRules {}
+
+impl RedactionRules {
+    /// Create the default rules (redacting home directory and user name)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the home directory (from `HOME`, or `USERPROFILE` on Windows)
+    /// with `~`
+    ///
+    /// Other paths that merely start with the same text (such as
+    /// `/home/alice2` for `/home/alice`) are left alone.
+    pub fn home(mut self, enabled: bool) -> Self {
+        self.home = enabled;
+        self
+    }
+
+    /// Replace the user name (from `USER`, or `USERNAME` on Windows) with
+    /// `<user>`
+    ///
+    /// Only whole words are replaced.
+    pub fn username(mut self, enabled: bool) -> Self {
+        self.username = enabled;
+        self
+    }
+
+    /// Replace all matches of `pattern` with `replacement`
+    ///
+    /// The replacement may refer to capture groups, see
+    /// [`Regex::replace_all`]. Patterns are applied in the order they were
+    /// added, after the home directory and user name.
+    #[cfg(feature = "regex")]
+    pub fn pattern(mut self, pattern: Regex, replacement: impl Into<String>) -> Self {
+        self.patterns.push((pattern, replacement.into()));
+        self
+    }
+
+    /// Apply the rules to a message
+    pub fn apply(&self, message: &str) -> String {
+        let mut message = message.to_owned();
+        if self.home {
+            if let Some(home) = env_value(if cfg!(windows) { "USERPROFILE" } else { "HOME" }) {
+                // Don't turn every `/` into `~` for the root user
+                let home = home.trim_end_matches(['/', '\\']);
+                if !home.is_empty() {
+                    message = replace_path(&message, home, "~");
+                }
+            }
+        }
+        if self.username {
+            if let Some(user) = env_value(if cfg!(windows) { "USERNAME" } else { "USER" }) {
+                message = replace_word(&message, &user, "<user>");
+            }
+        }
+        #[cfg(feature = "regex")]
+        for (pattern, replacement) in &self.patterns {
+            message = pattern
+                .replace_all(&message, replacement.as_str())
+                .into_owned();
+        }
+        message
+    }
+
+    /// Apply the rules to everything in an outcome that comes from the check
+    /// (the message, details, extended output and hint)
+    ///
+    /// This is done automatically when using
+    /// [`MedicOptions::redact`](crate::MedicOptions::redact).
+    pub fn apply_to(&self, outcome: &mut CheckOutcome) {
+        outcome.message = self.apply(&outcome.message);
+        for (_, value) in &mut outcome.details {
+            *value = self.apply(value);
+        }
+        if let Some(extended) = &mut outcome.extended {
+            *extended = self.apply(extended);
+        }
+        if let Some(hint) = &mut outcome.hint {
+            *hint = self.apply(hint);
+        }
+    }
+}
+
+/// Replace whole word occurrences of `word` in `message`
+///
+/// Word characters are alphanumeric characters and `_` (like `\w` in a
+/// regex).
+pub(crate) fn replace_word(message: &str, word: &str, replacement: &str) -> String {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    replace_matches(message, word, replacement, |before, after| {
+        before.map_or(true, |c| !is_word_char(c))
+            && after.chars().next().map_or(true, |c| !is_word_char(c))
+    })
+}
+
+/// Replace occurrences of the path `path` in `message`, but not where it is
+/// a prefix of another path (such as `/home/alice2` for `/home/alice`)
+pub(crate) fn replace_path(message: &str, path: &str, replacement: &str) -> String {
+    let is_name_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-');
+    replace_matches(message, path, replacement, |_, after| {
+        let mut chars = after.chars();
+        match chars.next() {
+            None => true,
+            // A trailing `.` may end a sentence rather than continue the name
+            Some('.') => !chars.next().is_some_and(is_name_char),
+            Some(c) => !is_name_char(c),
+        }
+    })
+}
+
+/// Replace the occurrences of `needle` in `message` that are accepted by
+/// `accept`, which gets the character before the match and the text after it
+fn replace_matches(
+    message: &str,
+    needle: &str,
+    replacement: &str,
+    accept: impl Fn(Option<char>, &str) -> bool,
+) -> String {
+    if needle.is_empty() {
+        return message.to_owned();
+    }
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(idx) = rest.find(needle) {
+        let (before, after) = (&rest[..idx], &rest[idx + needle.len()..]);
+        let previous = before
+            .chars()
+            .next_back()
+            .or_else(|| result.chars().next_back());
+        result.push_str(before);
+        if accept(previous, after) {
+            result.push_str(replacement);
+        } else {
+            result.push_str(needle);
+        }
+        rest = after;
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Get a non-empty environment variable
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
@@ -0,0 +1,40 @@
+//! Registry of checks declared with the [`check`](crate::check) attribute
+
+use crate::Check;
+
+/// A check registered with the [`check`](crate::check) attribute
+///
+/// Not public API, only used by the generated code.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct RegisteredCheck(Check);
+
+impl RegisteredCheck {
+    #[doc(hidden)]
+    pub const fn new(check: Check) -> Self {
+        Self(check)
+    }
+}
+
+inventory::collect!(RegisteredCheck);
+
+/// Get all checks registered with the [`check`](crate::check) attribute
+///
+/// This includes checks from all crates linked into the program. The checks
+/// are sorted by name, as the order they were registered in isn't known.
+///
+/// ```
+/// let mut stdout = anstream::stdout();
+/// let checks = medic::registered_checks();
+/// let worst = medic::medic(&mut stdout, checks.into_iter())?;
+/// medic::summary(&mut stdout, worst)?;
+/// # Ok::<(), medic::MedicError>(())
+/// ```
+pub fn registered_checks() -> Vec<&'static Check> {
+    let mut checks: Vec<&'static Check> = inventory::iter::<RegisteredCheck>
+        .into_iter()
+        .map(|registered| &registered.0)
+        .collect();
+    checks.sort_by_key(|check| check.name);
+    checks
+}
@@ -0,0 +1,572 @@
+//! Rendering of check outcomes
+
+use crate::Check;
+use crate::CheckOutcome;
+use crate::CheckProgress;
+use crate::CheckResult;
+use crate::MedicError;
+use anstyle::Effects;
+use anstyle::Reset;
+use std::borrow::Cow;
+use std::cmp::max;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
+
+/// Column layout computed from all outcomes before rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableLayout {
+    /// Width of the RESULT column
+    pub result_width: usize,
+    /// Width of the CHECK column
+    pub name_width: usize,
+    /// Width of the TIME column (zero if no outcome has a duration, in which
+    /// case the column is not shown)
+    pub time_width: usize,
+}
+
+impl TableLayout {
+    /// Compute the layout needed to fit all the outcomes (and the headers)
+    pub fn new(outcomes: &[CheckOutcome]) -> Self {
+        let mut result_width = "RESULT".len();
+        let mut name_width = "CHECK".len();
+        for outcome in outcomes {
+            result_width = max(
+                result_width,
+                <&CheckResult as Into<&str>>::into(&outcome.result).len(),
+            );
+            name_width = max(name_width, outcome.name.width());
+        }
+        let time_width = outcomes
+            .iter()
+            .filter_map(|outcome| outcome.duration)
+            .map(|duration| max("TIME".len(), format_duration(duration).len()))
+            .max()
+            .unwrap_or(0);
+        Self {
+            result_width,
+            name_width,
+            time_width,
+        }
+    }
+
+    /// Compute the layout before running the checks, based only on the names
+    ///
+    /// The RESULT column is made wide enough for any result, and there is no
+    /// TIME column.
+    pub(crate) fn for_checks(checks: &[&Check]) -> Self {
+        let result_width = [
+            CheckResult::Skipped,
+            CheckResult::Ok,
+            CheckResult::Info,
+            CheckResult::Warning,
+            CheckResult::Error,
+            CheckResult::Fatal,
+        ]
+        .iter()
+        .map(|result| <&CheckResult as Into<&str>>::into(result).len())
+        .fold("RESULT".len(), max);
+        let name_width = checks
+            .iter()
+            .map(|check| check.name.width())
+            .fold("CHECK".len(), max);
+        Self {
+            result_width,
+            name_width,
+            time_width: 0,
+        }
+    }
+
+    /// Column at which the MESSAGE column starts
+    pub const fn message_column(&self) -> usize {
+        let time_column = if self.time_width > 0 {
+            self.time_width + 2
+        } else {
+            0
+        };
+        self.result_width + self.name_width + time_column + 4
+    }
+}
+
+/// Format a duration for display
+fn format_duration(duration: Duration) -> String {
+    format!("{duration:.1?}")
+}
+
+/// Pad text with spaces to the given display width
+///
+/// Unlike the padding of `format!` this takes wide characters (such as CJK
+/// and emoji) into account.
+pub(crate) fn pad(text: &str, width: usize) -> String {
+    format!("{text}{:1$}", "", width.saturating_sub(text.width()))
+}
+
+/// Make text from checks safe to show in the terminal
+///
+/// Tabs are expanded (to tab stops every 8 columns), a carriage return before
+/// a line feed is removed, and any other control characters (such as escape
+/// sequences from command output) are shown escaped, as they would break the
+/// alignment of the table or mess up the terminal.
+fn sanitize(text: &str) -> Cow<'_, str> {
+    const TAB_WIDTH: usize = 8;
+    if !text.chars().any(|chr| chr.is_control() && chr != '\n') {
+        return Cow::Borrowed(text);
+    }
+    let mut sanitized = String::with_capacity(text.len());
+    let mut column = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '\n' => {
+                sanitized.push('\n');
+                column = 0;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\t' => {
+                let spaces = TAB_WIDTH - column % TAB_WIDTH;
+                sanitized.extend(std::iter::repeat(' ').take(spaces));
+                column += spaces;
+            }
+            chr if chr.is_control() => {
+                let escaped = chr.escape_default().to_string();
+                column += escaped.len();
+                sanitized.push_str(&escaped);
+            }
+            chr => {
+                column += chr.width().unwrap_or(0);
+                sanitized.push(chr);
+            }
+        }
+    }
+    Cow::Owned(sanitized)
+}
+
+/// Presentation of outcomes, driven by [`render_with`] or
+/// [`medic_with_renderer`](crate::medic_with_renderer)
+///
+/// All checks are run (and the layout computed) before any rendering
+/// happens. Then [`RowRenderer::render_header`] is called once, followed by
+/// [`RowRenderer::render_row`] for each outcome in order.
+pub trait RowRenderer {
+    /// Render anything that should come before the rows (does nothing by
+    /// default)
+    fn render_header(
+        &self,
+        _layout: &TableLayout,
+        _output: &mut impl Write,
+    ) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    /// Render a single outcome
+    fn render_row(
+        &self,
+        outcome: &CheckOutcome,
+        layout: &TableLayout,
+        output: &mut impl Write,
+    ) -> Result<(), std::io::Error>;
+}
+
+/// The standard aligned table (as used by [`medic`](crate::medic))
+///
+/// By default long messages are not wrapped. Use
+/// [`TableRenderer::with_width`] or [`TableRenderer::with_terminal_width`] to
+/// wrap the MESSAGE column to fit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableRenderer {
+    width: Option<usize>,
+}
+
+impl TableRenderer {
+    /// Create a table renderer that doesn't wrap messages
+    pub const fn new() -> Self {
+        Self { width: None }
+    }
+
+    /// Wrap messages so that rows fit within `width` columns
+    ///
+    /// Wrapping happens at word boundaries where possible, words longer than
+    /// the available space are split. `None` disables wrapping.
+    pub const fn with_width(self, width: Option<usize>) -> Self {
+        Self { width }
+    }
+
+    /// Get the width rows are wrapped to, if any
+    pub const fn width(&self) -> Option<usize> {
+        self.width
+    }
+
+    /// Wrap messages to the width of the terminal
+    ///
+    /// If stdout isn't a terminal (e.g. when output is piped to a file) or
+    /// the width can't be determined, no wrapping is done.
+    pub fn with_terminal_width(self) -> Self {
+        let width = terminal_size::terminal_size_of(std::io::stdout())
+            .map(|(terminal_size::Width(width), _)| usize::from(width));
+        self.with_width(width)
+    }
+
+    /// Split the message into lines, wrapping as configured
+    fn message_lines<'msg>(&self, message: &'msg str, layout: &TableLayout) -> Vec<&'msg str> {
+        // If the space left is too narrow it is better to just overflow
+        const MIN_MESSAGE_WIDTH: usize = 10;
+        let available = self
+            .width
+            .map(|width| width.saturating_sub(layout.message_column()))
+            .filter(|available| *available >= MIN_MESSAGE_WIDTH);
+        match available {
+            None => message.split('\n').collect(),
+            Some(available) => message
+                .split('\n')
+                .flat_map(|line| wrap_line(line, available))
+                .collect(),
+        }
+    }
+}
+
+/// Wrap a single line to the given display width (in terminal columns)
+///
+/// Lines that already fit are returned unchanged.
+fn wrap_line(line: &str, width: usize) -> Vec<&str> {
+    let mut lines = vec![];
+    let mut rest = line;
+    while rest.width() > width {
+        // Find the byte index of the first character that doesn't fit in
+        // `width` columns, as well as the last space that we can break at
+        let mut limit = rest.len();
+        let mut last_space = None;
+        let mut used = 0;
+        for (idx, chr) in rest.char_indices() {
+            if chr == ' ' && idx > 0 {
+                last_space = Some(idx);
+            }
+            used += chr.width().unwrap_or(0);
+            if used > width {
+                // Always make progress, even if a single character is too wide
+                limit = max(idx, chr.len_utf8());
+                break;
+            }
+        }
+        let (head, tail) = match last_space {
+            Some(idx) => (&rest[..idx], &rest[idx + 1..]),
+            None => rest.split_at(limit),
+        };
+        lines.push(head.trim_end());
+        rest = tail.trim_start();
+    }
+    lines.push(rest);
+    lines
+}
+
+impl RowRenderer for TableRenderer {
+    fn render_header(
+        &self,
+        layout: &TableLayout,
+        output: &mut impl Write,
+    ) -> Result<(), std::io::Error> {
+        let status_width = layout.result_width;
+        let name_width = layout.name_width;
+        let time = match layout.time_width {
+            0 => String::new(),
+            time_width => format!("{: <time_width$}  ", "TIME"),
+        };
+        writeln!(
+            output,
+            "{}{: <status_width$}  {: <name_width$}  {time}MESSAGE{}",
+            Effects::BOLD.render(),
+            "RESULT",
+            "CHECK",
+            Reset.render()
+        )
+    }
+
+    fn render_row(
+        &self,
+        outcome: &CheckOutcome,
+        layout: &TableLayout,
+        output: &mut impl Write,
+    ) -> Result<(), std::io::Error> {
+        let status_width = layout.result_width;
+        let name_width = layout.name_width;
+        let key_width = outcome
+            .details
+            .iter()
+            .map(|(key, _)| key.width() + 1)
+            .max()
+            .unwrap_or_default();
+        let details: Vec<_> = outcome
+            .details
+            .iter()
+            .map(|(key, value)| {
+                let key = sanitize(key) + ":";
+                format!("{}  {}", pad(&key, key_width), sanitize(value))
+            })
+            .collect();
+        let message = sanitize(&outcome.message);
+        let extended = outcome.extended.as_deref().map(sanitize);
+        let hint = outcome
+            .hint
+            .as_deref()
+            .map(|hint| format!("Hint: {}", sanitize(hint)));
+        let text = self
+            .message_lines(&message, layout)
+            .into_iter()
+            .chain(
+                details
+                    .iter()
+                    .flat_map(|detail| self.message_lines(detail, layout)),
+            )
+            .chain(
+                extended
+                    .iter()
+                    .flat_map(|extended| self.message_lines(extended, layout)),
+            )
+            .chain(
+                hint.iter()
+                    .flat_map(|hint| self.message_lines(hint, layout)),
+            )
+            .collect::<Vec<_>>()
+            .join(&("\n".to_owned() + " ".repeat(layout.message_column()).as_str()));
+        let time = match layout.time_width {
+            0 => String::new(),
+            time_width => format!(
+                "{: <time_width$}  ",
+                outcome.duration.map(format_duration).unwrap_or_default()
+            ),
+        };
+        writeln!(
+            output,
+            "{: <status_width$}  {}  {time}{text}",
+            outcome.result,
+            pad(outcome.name, name_width)
+        )
+    }
+}
+
+/// Output format for [`MedicReport`](crate::MedicReport)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OutputFormat {
+    /// Aligned table (see [`render_table`])
+    #[default]
+    Table,
+    /// GitHub flavoured Markdown table (see [`render_markdown`])
+    Markdown,
+    /// JSON document (see `render_json`, requires the `serde` feature)
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+/// GitHub flavoured Markdown table (as used by
+/// [`medic_markdown`](crate::medic_markdown))
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MarkdownRenderer;
+
+impl MarkdownRenderer {
+    /// Escape text for use in a table cell
+    fn escape(text: &str) -> String {
+        sanitize(text).replace('|', "\\|").replace('\n', "<br>")
+    }
+}
+
+impl RowRenderer for MarkdownRenderer {
+    fn render_header(
+        &self,
+        _layout: &TableLayout,
+        output: &mut impl Write,
+    ) -> Result<(), std::io::Error> {
+        writeln!(output, "| RESULT | CHECK | MESSAGE |")?;
+        writeln!(output, "|--------|-------|---------|")
+    }
+
+    fn render_row(
+        &self,
+        outcome: &CheckOutcome,
+        _layout: &TableLayout,
+        output: &mut impl Write,
+    ) -> Result<(), std::io::Error> {
+        let result: &'static str = outcome.result.into();
+        let details: String = outcome
+            .details
+            .iter()
+            .map(|(key, value)| format!("<br>{}: {}", Self::escape(key), Self::escape(value)))
+            .collect();
+        let extended = outcome
+            .extended
+            .as_deref()
+            .map(|extended| format!("<br>{}", Self::escape(extended)))
+            .unwrap_or_default();
+        let hint = outcome
+            .hint
+            .as_deref()
+            .map(|hint| format!("<br>Hint: {}", Self::escape(hint)))
+            .unwrap_or_default();
+        writeln!(
+            output,
+            "| {result} | {} | {}{details}{extended}{hint} |",
+            Self::escape(outcome.name),
+            Self::escape(&outcome.message)
+        )
+    }
+}
+
+/// Progress callbacks rendering each row as soon as it (and all rows before
+/// it) is done
+pub(crate) struct IncrementalTable<'out, W> {
+    renderer: TableRenderer,
+    layout: TableLayout,
+    state: Mutex<IncrementalState<'out, W>>,
+}
+
+/// Mutable state of [`IncrementalTable`]
+struct IncrementalState<'out, W> {
+    output: &'out mut W,
+    /// Index of the next row to render
+    next: usize,
+    /// Rows that are done, but can't be rendered yet
+    pending: BTreeMap<usize, CheckOutcome>,
+    /// First error encountered when writing
+    error: Option<std::io::Error>,
+}
+
+impl<'out, W: Write + Send> IncrementalTable<'out, W> {
+    /// Render the header, and prepare for rendering rows
+    pub(crate) fn new(
+        output: &'out mut W,
+        renderer: TableRenderer,
+        layout: TableLayout,
+    ) -> Result<Self, MedicError> {
+        renderer.render_header(&layout, output)?;
+        Ok(Self {
+            renderer,
+            layout,
+            state: Mutex::new(IncrementalState {
+                output,
+                next: 0,
+                pending: BTreeMap::new(),
+                error: None,
+            }),
+        })
+    }
+
+    /// Get the first error encountered when writing, if any
+    pub(crate) fn finish(self) -> Result<(), MedicError> {
+        let state = self
+            .state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        match state.error {
+            Some(err) => Err(err.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write + Send> CheckProgress for IncrementalTable<'_, W> {
+    fn check_done(&self, index: usize, outcome: &CheckOutcome) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = &mut *state;
+        state.pending.insert(index, outcome.clone());
+        while let Some(outcome) = state.pending.remove(&state.next) {
+            state.next += 1;
+            if state.error.is_none() {
+                let rendered = self
+                    .renderer
+                    .render_row(&outcome, &self.layout, state.output)
+                    .and_then(|()| state.output.flush());
+                state.error = rendered.err();
+            }
+        }
+    }
+}
+
+/// Render outcomes as the standard aligned table (as used by [`medic`](crate::medic))
+///
+/// Like [`medic`](crate::medic) this always writes colour codes, see
+/// [Colours](crate#colours).
+pub fn render_table(output: &mut impl Write, outcomes: &[CheckOutcome]) -> Result<(), MedicError> {
+    render_with(output, outcomes, &TableRenderer::new())
+}
+
+/// Render outcomes using the given renderer
+pub fn render_with(
+    output: &mut impl Write,
+    outcomes: &[CheckOutcome],
+    renderer: &impl RowRenderer,
+) -> Result<(), MedicError> {
+    let layout = TableLayout::new(outcomes);
+    renderer.render_header(&layout, output)?;
+    for outcome in outcomes {
+        renderer.render_row(outcome, &layout, output)?;
+    }
+    Ok(())
+}
+
+/// Render outcomes as a GitHub flavoured Markdown table
+///
+/// This is the same format as produced by
+/// [`medic_markdown`](crate::medic_markdown).
+pub fn render_markdown(
+    output: &mut impl Write,
+    outcomes: &[CheckOutcome],
+) -> Result<(), MedicError> {
+    render_with(output, outcomes, &MarkdownRenderer)
+}
+
+/// Render outcomes as a Markdown table inside a collapsible `<details>` block
+///
+/// This keeps long output from cluttering up issues. The `title` is shown
+/// when the block is collapsed, and is included verbatim (so it may contain
+/// HTML).
+pub fn render_markdown_details(
+    output: &mut impl Write,
+    outcomes: &[CheckOutcome],
+    title: &str,
+) -> Result<(), MedicError> {
+    // The blank lines are required for GitHub to render the Markdown inside
+    writeln!(output, "<details>\n<summary>{title}</summary>\n")?;
+    render_markdown(output, outcomes)?;
+    writeln!(output, "\n</details>")?;
+    Ok(())
+}
+
+/// Render outcomes as a JSON document
+///
+/// This is the same format as produced by [`medic_json`](crate::medic_json),
+/// with `worst` being the worst result among the given outcomes.
+#[cfg(feature = "serde")]
+pub fn render_json(output: &mut impl Write, outcomes: &[CheckOutcome]) -> Result<(), MedicError> {
+    render_json_report(output, outcomes, crate::worst_result(outcomes))
+}
+
+/// Render outcomes as a JSON document with the given `worst` result
+///
+/// This allows `worst` to take outcomes into account that are not included
+/// in the document (such as ones hidden by [`MedicOptions::min_level`](crate::MedicOptions::min_level)).
+#[cfg(feature = "serde")]
+pub(crate) fn render_json_report(
+    output: &mut impl Write,
+    outcomes: &[CheckOutcome],
+    worst: CheckResult,
+) -> Result<(), MedicError> {
+    #[derive(serde::Serialize)]
+    struct Report<'a> {
+        worst: CheckResult,
+        checks: &'a [CheckOutcome],
+    }
+
+    serde_json::to_writer_pretty(
+        &mut *output,
+        &Report {
+            worst,
+            checks: outcomes,
+        },
+    )
+    .map_err(std::io::Error::from)?;
+    writeln!(output)?;
+    Ok(())
+}
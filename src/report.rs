@@ -0,0 +1,206 @@
+//! Builder for assembling checks incrementally
+
+use crate::render_markdown;
+use crate::render_with;
+use crate::run_checks_with;
+use crate::summary;
+use crate::summary_markdown;
+use crate::worst_result;
+use crate::Check;
+use crate::CheckOutcome;
+use crate::CheckResult;
+use crate::MedicError;
+use crate::MedicOptions;
+use crate::OutputFormat;
+use anstream::AutoStream;
+use anstream::ColorChoice;
+use anstream::StripStream;
+use std::io::Write;
+
+/// A set of checks, built up incrementally
+///
+/// This is useful when checks are included conditionally (based on feature
+/// flags or runtime detection), and owns the checks so that checks with
+/// captured state work without any lifetime issues.
+///
+/// It also collects all configuration in one place: how the checks are run
+/// ([`MedicReport::options`]), which results are shown
+/// ([`MedicReport::min_severity`]) and how ([`MedicReport::format`]).
+///
+/// ```
+/// use medic::checks;
+/// use medic::MedicReport;
+///
+/// let mut report = MedicReport::new();
+/// report
+///     .add(checks::CHECK_RUSTC_VERSION)
+///     .add(checks::CHECK_HOST);
+/// if cfg!(target_os = "linux") {
+///     report.add(checks::check_in_path("sh"));
+/// }
+/// let worst = report.run_summary(&mut anstream::stdout())?;
+/// # Ok::<(), medic::MedicError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct MedicReport {
+    checks: Vec<Check>,
+    options: MedicOptions,
+    format: OutputFormat,
+    color: ColorChoice,
+}
+
+impl MedicReport {
+    /// Create an empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a check
+    pub fn add(&mut self, check: Check) -> &mut Self {
+        self.checks.push(check);
+        self
+    }
+
+    /// Add multiple checks
+    pub fn add_all(&mut self, checks: impl IntoIterator<Item = Check>) -> &mut Self {
+        self.checks.extend(checks);
+        self
+    }
+
+    /// Set the options used when running the checks
+    pub fn options(&mut self, options: MedicOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Only show results at or above `min_level`
+    ///
+    /// This is a shorthand for [`MedicOptions::min_level`] on the current
+    /// options.
+    pub fn min_severity(&mut self, min_level: CheckResult) -> &mut Self {
+        self.options = self.options.clone().min_level(min_level);
+        self
+    }
+
+    /// Set the output format (the default is [`OutputFormat::Table`])
+    pub fn format(&mut self, format: OutputFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Set when to use colours (the default is [`ColorChoice::Auto`])
+    ///
+    /// With [`ColorChoice::Auto`], colours are used if stdout is a terminal,
+    /// honouring `NO_COLOR`, `CLICOLOR` and `CLICOLOR_FORCE` (see
+    /// [`anstream`]). This is based on stdout even if the output goes
+    /// elsewhere. There is no need to wrap the output in an
+    /// [`anstream::AutoStream`].
+    pub fn color(&mut self, color: ColorChoice) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    /// Get the checks added so far
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+
+    /// Run all checks and print the table (see [`medic`](crate::medic))
+    ///
+    /// Returns [`MedicError::DuplicateChecks`] (without running anything) if
+    /// multiple checks have the same name.
+    pub fn run(&self, output: &mut impl Write) -> Result<CheckResult, MedicError> {
+        let outcomes = self.run_outcomes(output)?;
+        Ok(worst_result(&outcomes))
+    }
+
+    /// Run all checks and print the table, returning all the outcomes
+    ///
+    /// This is like [`MedicReport::run`], but allows further processing of
+    /// the results. Outcomes hidden from the output (see
+    /// [`MedicOptions::min_level`]) are included.
+    ///
+    /// With JSON output (`OutputFormat::Json`) a document is always written,
+    /// even in [`MedicOptions::quiet`] mode (with no rows), and its `worst`
+    /// field includes hidden outcomes.
+    pub fn run_outcomes(&self, output: &mut impl Write) -> Result<Vec<CheckOutcome>, MedicError> {
+        self.ensure_unique()?;
+        let outcomes = run_checks_with(self.checks.iter(), &self.options);
+        let shown = self.options.visible(&outcomes);
+        // A JSON document is always written (even if every row is hidden), so
+        // that the output can always be parsed
+        #[cfg(feature = "serde")]
+        let shown = if self.format == OutputFormat::Json {
+            Some(shown.unwrap_or_default())
+        } else {
+            shown
+        };
+        if let Some(shown) = shown {
+            self.with_color(output, |mut output| match self.format {
+                OutputFormat::Table => {
+                    render_with(&mut output, &shown, &self.options.table_renderer())
+                }
+                OutputFormat::Markdown => render_markdown(&mut output, &shown),
+                #[cfg(feature = "serde")]
+                OutputFormat::Json => {
+                    crate::render::render_json_report(&mut output, &shown, worst_result(&outcomes))
+                }
+            })?;
+        }
+        Ok(outcomes)
+    }
+
+    /// Run all checks, then print the table and the [`summary`]
+    ///
+    /// With [`OutputFormat::Markdown`] the summary is printed with
+    /// [`summary_markdown`] instead, and JSON output has no summary.
+    pub fn run_summary(&self, output: &mut impl Write) -> Result<CheckResult, MedicError> {
+        let worst = self.run(output)?;
+        self.with_color(output, |mut output| match self.format {
+            OutputFormat::Table => summary(&mut output, worst),
+            OutputFormat::Markdown => summary_markdown(&mut output, worst),
+            #[cfg(feature = "serde")]
+            OutputFormat::Json => Ok(()),
+        })?;
+        Ok(worst)
+    }
+
+    /// Write to `output`, stripping colours unless they should be used
+    fn with_color(
+        &self,
+        output: &mut impl Write,
+        write: impl FnOnce(&mut dyn Write) -> Result<(), MedicError>,
+    ) -> Result<(), MedicError> {
+        let choice = match self.color {
+            ColorChoice::Auto => AutoStream::choice(&std::io::stdout()),
+            choice => choice,
+        };
+        match choice {
+            ColorChoice::Always | ColorChoice::AlwaysAnsi => write(output),
+            ColorChoice::Never | ColorChoice::Auto => {
+                let mut stripped = StripStream::new(Vec::new());
+                write(&mut stripped)?;
+                output.write_all(&stripped.into_inner())?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Check that the names of all checks are unique
+    fn ensure_unique(&self) -> Result<(), MedicError> {
+        let mut duplicates: Vec<String> = vec![];
+        for (idx, check) in self.checks.iter().enumerate() {
+            let seen_before = self.checks[..idx]
+                .iter()
+                .any(|other| other.name == check.name);
+            if seen_before && !duplicates.iter().any(|name| name == check.name) {
+                duplicates.push(check.name.to_owned());
+            }
+        }
+        if duplicates.is_empty() {
+            Ok(())
+        } else {
+            Err(MedicError::DuplicateChecks(duplicates))
+        }
+    }
+}
@@ -0,0 +1,484 @@
+//! Running of checks
+
+use crate::Check;
+use crate::CheckFunc;
+use crate::CheckOutcome;
+use crate::CheckResult;
+use crate::DetailedResult;
+use crate::RedactionRules;
+use crate::TableRenderer;
+use std::cmp::max;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Options controlling how checks are run
+///
+/// Used with [`medic_with_options`](crate::medic_with_options) and
+/// [`run_checks_with`]. The defaults match [`medic`](crate::medic).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MedicOptions {
+    timing: bool,
+    slow_threshold: Option<Duration>,
+    check_timeout: Option<Duration>,
+    pub(crate) parallel: bool,
+    min_level: Option<CheckResult>,
+    quiet: bool,
+    pub(crate) redaction: Option<RedactionRules>,
+    verbose: bool,
+    width: Option<usize>,
+    deadline: Option<Instant>,
+}
+
+impl MedicOptions {
+    /// Create options with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Measure how long each check takes to run
+    ///
+    /// The time is stored in [`CheckOutcome::duration`], and is shown in a
+    /// TIME column by [`TableRenderer`](crate::TableRenderer).
+    pub fn timing(mut self, enabled: bool) -> Self {
+        self.timing = enabled;
+        self
+    }
+
+    /// Raise checks that take longer than `threshold` to at least
+    /// [`CheckResult::Info`], so that they stand out
+    ///
+    /// The time taken is appended to the message of such checks (e.g.
+    /// `(slow: 2.3s)`).
+    ///
+    /// This implies [`MedicOptions::timing`].
+    pub fn slow_threshold(mut self, threshold: Duration) -> Self {
+        self.timing = true;
+        self.slow_threshold = Some(threshold);
+        self
+    }
+
+    /// Give up on checks that take longer than `timeout`, reporting them as
+    /// [`CheckResult::Fatal`]
+    ///
+    /// Each check is then run on a separate thread. A check that times out is
+    /// left running in the background (there is no way to safely stop a
+    /// thread), but no longer blocks the remaining checks.
+    pub fn check_timeout(mut self, timeout: Duration) -> Self {
+        self.check_timeout = Some(timeout);
+        self
+    }
+
+    /// Run checks concurrently (one thread per check)
+    ///
+    /// This is useful when checks spawn subprocesses or do I/O. The outcomes
+    /// are still reported in the original order.
+    pub fn parallel(mut self, enabled: bool) -> Self {
+        self.parallel = enabled;
+        self
+    }
+
+    /// Only show results at or above `min_level`
+    ///
+    /// The worst level is still computed across *all* checks, so a hidden
+    /// result still affects the [`summary`](crate::summary). See also
+    /// [`medic_filtered`](crate::medic_filtered).
+    pub fn min_level(mut self, min_level: CheckResult) -> Self {
+        self.min_level = Some(min_level);
+        self
+    }
+
+    /// Don't show the table at all unless a problem
+    /// ([`CheckResult::Warning`] or worse) is found
+    ///
+    /// This is useful when the checks are run automatically (such as at
+    /// startup), where showing all results every time would be too noisy.
+    pub fn quiet(mut self, enabled: bool) -> Self {
+        self.quiet = enabled;
+        self
+    }
+
+    /// Remove sensitive data from all check messages before they are rendered
+    ///
+    /// See [`RedactionRules`]. This also applies to asynchronous checks run with
+    /// `run_checks_async_with` (with the `async` feature).
+    pub fn redact(mut self, rules: RedactionRules) -> Self {
+        self.redaction = Some(rules);
+        self
+    }
+
+    /// Collect and show extended output of checks (see
+    /// [`DetailedResult::extended`])
+    ///
+    /// This is suitable for a `-v` flag, such as `--doctor -v`.
+    pub fn verbose(mut self, enabled: bool) -> Self {
+        self.verbose = enabled;
+        self
+    }
+
+    /// Wrap messages in the table so that rows fit within `width` columns
+    ///
+    /// See [`TableRenderer::with_width`], `None` (the default) disables
+    /// wrapping. Use [`MedicOptions::terminal_width`] to wrap to the width of
+    /// the terminal.
+    pub fn width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Wrap messages in the table to the width of the terminal
+    ///
+    /// See [`TableRenderer::with_terminal_width`].
+    pub fn terminal_width(mut self) -> Self {
+        self.width = TableRenderer::new().with_terminal_width().width();
+        self
+    }
+
+    /// Skip checks that would start after `deadline` (`None` for no deadline)
+    ///
+    /// Used by [`medic_deadline`](crate::medic_deadline).
+    pub(crate) const fn deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Get the table renderer to use with these options
+    pub(crate) const fn table_renderer(&self) -> TableRenderer {
+        TableRenderer::new().with_width(self.width)
+    }
+
+    /// Select the outcomes to show, `None` if nothing should be shown
+    pub(crate) fn visible(&self, outcomes: &[CheckOutcome]) -> Option<Vec<CheckOutcome>> {
+        if self.quiet && worst_actionable(outcomes) < CheckResult::Warning {
+            return None;
+        }
+        Some(
+            outcomes
+                .iter()
+                .filter(|outcome| self.min_level.map_or(true, |min| outcome.result >= min))
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+/// Run checks without rendering anything, returning the outcomes
+///
+/// This allows processing the outcomes in other ways (logging, telemetry,
+/// etc) as well as rendering them (with [`render_table`](crate::render_table)
+/// or [`render_with`](crate::render_with)), without running the checks
+/// multiple times. Use [`worst_result`] to get the worst level found.
+pub fn run_checks<'iter>(checks: impl Iterator<Item = &'iter Check>) -> Vec<CheckOutcome> {
+    run_checks_with(checks, &MedicOptions::new())
+}
+
+/// Run checks with the given options, returning the outcomes
+///
+/// Checks with dependencies (see [`Check::depends_on`]) are run after the
+/// checks they depend on, and are [`CheckResult::Skipped`] unless all of
+/// those succeeded. The outcomes are always in the original order.
+///
+/// See [`run_checks`].
+pub fn run_checks_with<'iter>(
+    checks: impl Iterator<Item = &'iter Check>,
+    options: &MedicOptions,
+) -> Vec<CheckOutcome> {
+    run_checks_with_progress(checks, options, &NoProgress)
+}
+
+/// Callbacks for following the progress of running checks
+///
+/// Used with [`run_checks_with_progress`], e.g. to show a spinner. The index
+/// is the position of the check among the checks being run. With
+/// [`MedicOptions::parallel`] the callbacks are called from multiple threads.
+pub trait CheckProgress: Sync {
+    /// Called just before a check starts running (does nothing by default)
+    ///
+    /// This isn't called for checks that are skipped.
+    fn check_started(&self, _index: usize, _check: &Check) {}
+
+    /// Called when the outcome of a check is known (does nothing by default)
+    fn check_done(&self, _index: usize, _outcome: &CheckOutcome) {}
+}
+
+/// Progress callbacks that do nothing
+struct NoProgress;
+
+impl CheckProgress for NoProgress {}
+
+/// Run checks with the given options, reporting progress as checks start and
+/// finish
+///
+/// See [`run_checks_with`] and [`CheckProgress`].
+pub fn run_checks_with_progress<'iter>(
+    checks: impl Iterator<Item = &'iter Check>,
+    options: &MedicOptions,
+    progress: &impl CheckProgress,
+) -> Vec<CheckOutcome> {
+    let checks: Vec<&Check> = checks.collect();
+    let mut outcomes: Vec<Option<CheckOutcome>> = checks.iter().map(|_| None).collect();
+    loop {
+        // Run all checks whose dependencies are done, in "waves"
+        let mut ready = vec![];
+        for (idx, check) in checks.iter().enumerate() {
+            if outcomes[idx].is_some() {
+                continue;
+            }
+            match dependency_state(check, &checks, &outcomes) {
+                DependencyState::Pending => (),
+                DependencyState::Succeeded => ready.push(idx),
+                DependencyState::Failed(reason) => {
+                    let outcome = skipped(check, reason);
+                    progress.check_done(idx, &outcome);
+                    outcomes[idx] = Some(outcome);
+                }
+            }
+        }
+        if ready.is_empty() {
+            break;
+        }
+        let wave: Vec<(usize, &Check)> = ready.iter().map(|idx| (*idx, checks[*idx])).collect();
+        let results = if options.parallel {
+            run_parallel(&wave, options, progress)
+        } else {
+            wave.iter()
+                .map(|(idx, check)| run_with_progress(*idx, check, options, progress))
+                .collect()
+        };
+        for (idx, outcome) in ready.into_iter().zip(results) {
+            outcomes[idx] = Some(outcome);
+        }
+    }
+    // Anything left has dependencies that can never run (cycles)
+    checks
+        .iter()
+        .zip(outcomes)
+        .enumerate()
+        .map(|(idx, (check, outcome))| {
+            outcome.unwrap_or_else(|| {
+                let outcome = skipped(check, "Circular dependency".to_owned());
+                progress.check_done(idx, &outcome);
+                outcome
+            })
+        })
+        .collect()
+}
+
+/// Run a single check, reporting progress
+///
+/// The check is skipped instead if the deadline has passed.
+fn run_with_progress(
+    idx: usize,
+    check: &Check,
+    options: &MedicOptions,
+    progress: &impl CheckProgress,
+) -> CheckOutcome {
+    if options
+        .deadline
+        .is_some_and(|deadline| Instant::now() >= deadline)
+    {
+        let outcome = skipped(check, "Deadline exceeded".to_owned());
+        progress.check_done(idx, &outcome);
+        return outcome;
+    }
+    progress.check_started(idx, check);
+    let outcome = run_check(check, options);
+    progress.check_done(idx, &outcome);
+    outcome
+}
+
+/// State of the dependencies of a check
+enum DependencyState {
+    /// Some dependency has not run yet
+    Pending,
+    /// All dependencies succeeded (or there are none)
+    Succeeded,
+    /// Some dependency failed or will never run (with the reason)
+    Failed(String),
+}
+
+/// Get the state of the dependencies of a check
+fn dependency_state(
+    check: &Check,
+    checks: &[&Check],
+    outcomes: &[Option<CheckOutcome>],
+) -> DependencyState {
+    let mut state = DependencyState::Succeeded;
+    for dependency in check.dependencies {
+        let Some(idx) = checks.iter().position(|other| other.name == *dependency) else {
+            return DependencyState::Failed(format!("Depends on unknown check {dependency}"));
+        };
+        match &outcomes[idx] {
+            None => state = DependencyState::Pending,
+            Some(outcome) if matches!(outcome.result, CheckResult::Ok | CheckResult::Info) => (),
+            Some(outcome) => {
+                let result: &'static str = outcome.result.into();
+                return DependencyState::Failed(format!(
+                    "Skipped because {dependency} reported {result}"
+                ));
+            }
+        }
+    }
+    state
+}
+
+/// Outcome for a check that was skipped
+fn skipped(check: &Check, reason: String) -> CheckOutcome {
+    CheckOutcome {
+        name: check.name,
+        result: CheckResult::Skipped,
+        message: reason,
+        errored: false,
+        duration: None,
+        hint: None,
+        details: vec![],
+        extended: None,
+    }
+}
+
+/// Run checks concurrently (one thread per check), returning the outcomes in
+/// the original order
+///
+/// This is the same as [`run_checks_with`] with [`MedicOptions::parallel`].
+/// See also [`medic_parallel`](crate::medic_parallel).
+pub fn run_checks_parallel<'iter>(checks: impl Iterator<Item = &'iter Check>) -> Vec<CheckOutcome> {
+    run_checks_with(checks, &MedicOptions::new().parallel(true))
+}
+
+/// Run checks on one thread each
+fn run_parallel(
+    checks: &[(usize, &Check)],
+    options: &MedicOptions,
+    progress: &impl CheckProgress,
+) -> Vec<CheckOutcome> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = checks
+            .iter()
+            .map(|(idx, check)| scope.spawn(|| run_with_progress(*idx, check, options, progress)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                // Propagate panics the same way as when running serially
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+            })
+            .collect()
+    })
+}
+
+/// Run a single check, converting errors to [`CheckResult::Fatal`] (or as
+/// configured with [`Check::on_error`])
+pub(crate) fn run_check(check: &Check, options: &MedicOptions) -> CheckOutcome {
+    let start = Instant::now();
+    let outcome = match options.check_timeout {
+        Some(timeout) => run_with_timeout(check, timeout, options.verbose),
+        None => call(&check.func, options.verbose),
+    };
+    let duration = options.timing.then(|| start.elapsed());
+    let mut outcome = match outcome {
+        Ok((detailed, extended)) => CheckOutcome {
+            name: check.name,
+            result: detailed.result,
+            message: detailed.message,
+            errored: false,
+            duration,
+            hint: detailed.hint,
+            details: detailed.details,
+            extended,
+        },
+        Err(err) => CheckOutcome {
+            name: check.name,
+            result: check.error_result,
+            message: format!("{err}"),
+            errored: true,
+            duration,
+            hint: None,
+            details: vec![],
+            extended: None,
+        },
+    };
+    if outcome.result >= CheckResult::Warning {
+        outcome.hint = outcome.hint.or_else(|| check.hint.map(str::to_owned));
+    } else {
+        outcome.hint = None;
+    }
+    if let Some(rules) = &options.redaction {
+        rules.apply_to(&mut outcome);
+    }
+    if let (Some(duration), Some(threshold)) = (duration, options.slow_threshold) {
+        if duration > threshold {
+            outcome.result = max(outcome.result, CheckResult::Info);
+            outcome.message = format!("{} (slow: {duration:.1?})", outcome.message);
+        }
+    }
+    outcome
+}
+
+/// Result of calling a check function, along with the extended output
+type CallResult =
+    Result<(DetailedResult, Option<String>), Box<dyn std::error::Error + Send + Sync>>;
+
+/// Call a check function, collecting the extended output if `verbose`
+fn call(func: &CheckFunc, verbose: bool) -> CallResult {
+    let mut detailed = func.call()?;
+    let extended = detailed.take_extended(verbose);
+    Ok((detailed, extended))
+}
+
+/// Run a check on a separate thread, giving up after `timeout` (including
+/// the time taken to collect the extended output)
+fn run_with_timeout(check: &Check, timeout: Duration, verbose: bool) -> CallResult {
+    let func = check.func.clone();
+    let (sender, receiver) = mpsc::channel();
+    let handle = std::thread::spawn(move || {
+        // The receiver is gone if we already timed out
+        let _ = sender.send(call(&func, verbose));
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(outcome) => outcome,
+        Err(RecvTimeoutError::Timeout) => Err(format!("Timed out after {timeout:?}").into()),
+        Err(RecvTimeoutError::Disconnected) => {
+            // The check panicked, propagate that the same way as when running
+            // on the current thread
+            match handle.join() {
+                Err(payload) => std::panic::resume_unwind(payload),
+                Ok(()) => unreachable!("Check thread exited without a result"),
+            }
+        }
+    }
+}
+
+/// Get the worst result among the check outcomes
+///
+/// This is the highest severity seen, and as [`CheckResult::Info`] sorts above
+/// [`CheckResult::Ok`] a purely informational check makes this `Info` even
+/// though there is no problem. Use [`worst_actionable`] to only take problems
+/// into account.
+///
+/// This is [`CheckResult::Ok`] if there are no outcomes.
+pub fn worst_result(outcomes: &[CheckOutcome]) -> CheckResult {
+    outcomes
+        .iter()
+        .map(|outcome| outcome.result)
+        .fold(CheckResult::Ok, max)
+}
+
+/// Get the worst *problem* among the check outcomes
+///
+/// Unlike [`worst_result`] this treats [`CheckResult::Info`] as
+/// [`CheckResult::Ok`], so the result is `Ok` unless there is a warning or
+/// worse. If no check actually ran (there are no outcomes, or all of them were
+/// skipped) this is [`CheckResult::Skipped`], allowing "everything nominal" to
+/// be told apart from "nothing ran".
+pub fn worst_actionable(outcomes: &[CheckOutcome]) -> CheckResult {
+    outcomes
+        .iter()
+        .map(|outcome| match outcome.result {
+            CheckResult::Info => CheckResult::Ok,
+            result => result,
+        })
+        .fold(CheckResult::Skipped, max)
+}
@@ -0,0 +1,179 @@
+//! Summary line
+
+use crate::CheckResult;
+use crate::MedicError;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Configuration of the summary line
+///
+/// This controls at which level a summary is printed, and what the summary
+/// says. Messages are configured per level, and the message used is the one
+/// for the highest level not above the worst level found. For example, by
+/// default the [`CheckResult::Error`] message is used for
+/// [`CheckResult::Fatal`] too.
+///
+/// The default configuration matches [`summary`]:
+///
+/// * [`CheckResult::Warning`]: `Warning: Warning(s) found, consider
+///   investigating (especially if you have issues)`
+/// * [`CheckResult::Error`]: `Error: Error(s) found, you should rectify these
+///   for proper operation`
+///
+/// ```
+/// use medic::CheckResult;
+/// use medic::SummaryConfig;
+///
+/// // Treat warnings as something that must be fixed
+/// let config = SummaryConfig::new().message(
+///     CheckResult::Warning,
+///     "Warning",
+///     "Warning(s) found, these must be fixed",
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryConfig {
+    threshold: CheckResult,
+    messages: BTreeMap<CheckResult, (String, String)>,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SummaryConfig {
+    /// Create the default configuration
+    pub fn new() -> Self {
+        Self {
+            threshold: CheckResult::Warning,
+            messages: BTreeMap::new(),
+        }
+        .message(
+            CheckResult::Warning,
+            "Warning",
+            "Warning(s) found, consider investigating (especially if you have issues)",
+        )
+        .message(
+            CheckResult::Error,
+            "Error",
+            "Error(s) found, you should rectify these for proper operation",
+        )
+    }
+
+    /// Only print a summary if the worst level found is at least `threshold`
+    ///
+    /// The default is [`CheckResult::Warning`]. This is independent of the
+    /// threshold passed to [`threshold_exit_code`](crate::threshold_exit_code).
+    pub fn threshold(mut self, threshold: CheckResult) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Set the message for a level
+    ///
+    /// * `label`: Coloured prefix (e.g. `Warning`)
+    /// * `text`: The rest of the message
+    pub fn message(
+        mut self,
+        level: CheckResult,
+        label: impl Into<String>,
+        text: impl Into<String>,
+    ) -> Self {
+        self.messages.insert(level, (label.into(), text.into()));
+        self
+    }
+
+    /// Remove the message for a level
+    ///
+    /// The message for the next lower level (if any) will then be used
+    /// instead.
+    pub fn clear_message(mut self, level: CheckResult) -> Self {
+        self.messages.remove(&level);
+        self
+    }
+
+    /// Get the level, label and text of the summary for the worst level found
+    fn lookup(&self, worst_issues_found: CheckResult) -> Option<(CheckResult, &str, &str)> {
+        if worst_issues_found < self.threshold {
+            return None;
+        }
+        self.messages
+            .range(..=worst_issues_found)
+            .next_back()
+            .map(|(level, (label, text))| (*level, label.as_str(), text.as_str()))
+    }
+}
+
+/// Print summary line at the end
+///
+/// The level is coloured, so write to an [`anstream::AutoStream`] to strip the
+/// colour codes when the output isn't a terminal (see
+/// [Colours](crate#colours)).
+pub fn summary(output: &mut impl Write, worst_issues_found: CheckResult) -> Result<(), MedicError> {
+    summary_with_config(output, worst_issues_found, &SummaryConfig::new())
+}
+
+/// Print summary line at the end, but only if the worst level found is at
+/// least `threshold`
+///
+/// There are only summary messages for [`CheckResult::Warning`] and above (by
+/// default), so a lower threshold behaves the same as
+/// [`CheckResult::Warning`]. The threshold is independent of the one passed
+/// to [`threshold_exit_code`](crate::threshold_exit_code), allowing e.g.
+/// warnings to be reported without failing the process.
+pub fn summary_with_threshold(
+    output: &mut impl Write,
+    worst_issues_found: CheckResult,
+    threshold: CheckResult,
+) -> Result<(), MedicError> {
+    summary_with_config(
+        output,
+        worst_issues_found,
+        &SummaryConfig::new().threshold(threshold),
+    )
+}
+
+/// Print summary line at the end, using a custom configuration
+pub fn summary_with_config(
+    output: &mut impl Write,
+    worst_issues_found: CheckResult,
+    config: &SummaryConfig,
+) -> Result<(), MedicError> {
+    if let Some((level, label, text)) = config.lookup(worst_issues_found) {
+        let style = level.style();
+        writeln!(
+            output,
+            "\n{}{label}{}: {text}",
+            style.render(),
+            style.render_reset()
+        )?;
+    }
+    Ok(())
+}
+
+/// Print summary line at the end, formatted as Markdown
+///
+/// This is the counterpart of [`summary`] for use with
+/// [`medic_markdown`](crate::medic_markdown). The summary is emitted as a bold
+/// block quote, without any colour codes.
+pub fn summary_markdown(
+    output: &mut impl Write,
+    worst_issues_found: CheckResult,
+) -> Result<(), MedicError> {
+    summary_markdown_with_config(output, worst_issues_found, &SummaryConfig::new())
+}
+
+/// Print summary line at the end, formatted as Markdown, using a custom
+/// configuration
+pub fn summary_markdown_with_config(
+    output: &mut impl Write,
+    worst_issues_found: CheckResult,
+    config: &SummaryConfig,
+) -> Result<(), MedicError> {
+    if let Some((_, label, text)) = config.lookup(worst_issues_found) {
+        writeln!(output, "\n> **{label}: {text}**")?;
+    }
+    Ok(())
+}
@@ -1,23 +1,87 @@
+use crate::diff_reports;
+use crate::health_hint;
+use crate::health_score;
 use crate::medic;
+use crate::medic_deadline;
+use crate::medic_filtered;
+use crate::medic_fix;
+use crate::medic_grouped;
+use crate::medic_incremental;
+use crate::medic_markdown;
+use crate::medic_parallel;
+use crate::medic_require;
+use crate::medic_stream;
+use crate::medic_with_options;
+use crate::medic_with_renderer;
+use crate::render_diff;
+use crate::render_markdown_details;
+use crate::render_table;
+use crate::run_checks;
+use crate::run_checks_parallel;
+use crate::run_checks_with;
+use crate::summary;
+use crate::summary_markdown;
+use crate::summary_with_config;
+use crate::summary_with_threshold;
+use crate::threshold_exit_code;
+use crate::worst_actionable;
+use crate::worst_result;
 use crate::Check;
+use crate::CheckGroup;
+use crate::CheckOutcome;
 use crate::CheckResult;
+use crate::DetailedResult;
+use crate::ExitPolicy;
+use crate::HealthWeights;
+use crate::MedicError;
+use crate::MedicOptions;
+use crate::MedicReport;
+use crate::OutputFormat;
+use crate::RedactionRules;
+use crate::ReportChange;
+use crate::ResultCounts;
+use crate::RowRenderer;
+use crate::SavedReport;
+use crate::SummaryConfig;
+use crate::TableLayout;
+use crate::TableRenderer;
 use pretty_assertions::assert_eq;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Temporary directory for a test, removed when dropped (also when the test
+/// fails)
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("medic-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
 
 #[test]
 fn test_medic() {
     let checks = [
-        Check {
-            name: "Check 1",
-            func: || Ok((CheckResult::Ok, "All good".to_string())),
-        },
-        Check {
-            name: "Check 2",
-            func: || Ok((CheckResult::Warning, "Not so good\nNot at all".to_string())),
-        },
-        Check {
-            name: "Check 3",
-            func: || Ok((CheckResult::Fatal, "Very bad".to_string())),
-        },
+        Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+        Check::new("Check 2", || {
+            Ok((CheckResult::Warning, "Not so good\nNot at all".to_string()))
+        }),
+        Check::new("Check 3", || {
+            Ok((CheckResult::Fatal, "Very bad".to_string()))
+        }),
     ];
     // Get rid of formatting for ease of testing
     let mut out_buf = anstream::StripStream::new(Vec::new());
@@ -35,3 +99,2429 @@ fn test_medic() {
         Fatal    Check 3  Very bad\n"};
     assert_eq!(out, expected);
 }
+
+#[test]
+fn test_medic_require() {
+    let checks = [
+        Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+        Check::new("Check 2", || {
+            Ok((CheckResult::Info, "Some info".to_string()))
+        }),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = medic_require(&mut out_buf, checks.iter(), &["Check 1", "Check 2"]).unwrap();
+    assert_eq!(result, CheckResult::Info);
+
+    let err = medic_require(
+        &mut out_buf,
+        checks.iter(),
+        &["Check 1", "Check 3", "Check 4"],
+    )
+    .unwrap_err();
+    match err {
+        MedicError::MissingChecks(missing) => assert_eq!(missing, ["Check 3", "Check 4"]),
+        _ => panic!("Unexpected error: {err}"),
+    }
+
+    // Skipped checks didn't execute
+    let checks = [
+        Check::new("Installed", || {
+            Ok((CheckResult::Error, "Not found".to_string()))
+        }),
+        Check::new("Config", || unreachable!()).depends_on(&["Installed"]),
+        Check::new("Optional", || {
+            Ok((CheckResult::Skipped, "Not applicable".to_string()))
+        }),
+    ];
+    let err = medic_require(
+        &mut out_buf,
+        checks.iter(),
+        &["Installed", "Config", "Optional"],
+    )
+    .unwrap_err();
+    match err {
+        MedicError::MissingChecks(missing) => assert_eq!(missing, ["Config", "Optional"]),
+        _ => panic!("Unexpected error: {err}"),
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_sysctl() {
+    let check = crate::checks::sysctl("kernel.ostype", Some("Linux".to_string()));
+    assert_eq!(
+        check.run().unwrap(),
+        (CheckResult::Info, "Linux".to_string())
+    );
+
+    let check = crate::checks::sysctl("kernel.ostype", Some("Hurd".to_string()));
+    assert_eq!(
+        check.run().unwrap(),
+        (
+            CheckResult::Warning,
+            "Current value: Linux, expected: Hurd".to_string()
+        )
+    );
+
+    let check = crate::checks::sysctl("kernel.ostype", None);
+    assert_eq!(
+        check.run().unwrap(),
+        (CheckResult::Info, "Linux".to_string())
+    );
+}
+
+#[test]
+fn test_health_hint() {
+    let hint = |results: &[CheckResult]| {
+        let worst = results.iter().copied().max().unwrap_or(CheckResult::Ok);
+        let counts: ResultCounts = results.iter().copied().collect();
+        anstream::adapter::strip_str(&health_hint(worst, &counts)).to_string()
+    };
+    assert_eq!(hint(&[]), "[ok]");
+    assert_eq!(hint(&[CheckResult::Ok, CheckResult::Info]), "[ok]");
+    assert_eq!(
+        hint(&[CheckResult::Warning, CheckResult::Ok, CheckResult::Warning]),
+        "[2 warnings]"
+    );
+    assert_eq!(
+        hint(&[
+            CheckResult::Warning,
+            CheckResult::Error,
+            CheckResult::Warning
+        ]),
+        "[1 error, 2 warnings]"
+    );
+}
+
+#[test]
+fn test_line_endings() {
+    use crate::checks::line_endings;
+    use crate::checks::LineEnding;
+
+    let dir = TempDir::new("line-endings");
+    let path = dir.path().join("file");
+    let check = |data: &[u8], expected| {
+        std::fs::write(&path, data).unwrap();
+        line_endings(&path, expected, "line-endings").run().unwrap()
+    };
+
+    assert_eq!(
+        check(b"a\nb\nc\r\n", LineEnding::Lf),
+        (CheckResult::Ok, "Detected LF (LF: 2, CRLF: 1)".to_string())
+    );
+    assert_eq!(
+        check(b"a\r\nb\r\n", LineEnding::Lf),
+        (
+            CheckResult::Warning,
+            "Detected CRLF, expected LF (LF: 0, CRLF: 2)".to_string()
+        )
+    );
+    assert_eq!(check(b"no newline", LineEnding::CrLf).0, CheckResult::Info);
+}
+
+#[test]
+fn test_medic_deadline() {
+    let checks = [
+        Check::new("Slow", || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            Ok((CheckResult::Warning, "Took a while".to_string()))
+        }),
+        Check::new("Never", || Ok((CheckResult::Error, "Not run".to_string()))),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = medic_deadline(
+        &mut out_buf,
+        checks.iter(),
+        std::time::Duration::from_millis(10),
+        &MedicOptions::new(),
+    )
+    .unwrap();
+    assert_eq!(result, CheckResult::Warning);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK  MESSAGE
+        Warning  Slow   Took a while
+        Skipped  Never  Deadline exceeded\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_medic_deadline_timeout() {
+    let checks = [
+        Check::new("Hangs", || {
+            std::thread::sleep(std::time::Duration::from_secs(3600));
+            Ok((CheckResult::Ok, "Finally".to_string()))
+        }),
+        Check::new("Never", || Ok((CheckResult::Error, "Not run".to_string()))),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = medic_deadline(
+        &mut out_buf,
+        checks.iter(),
+        std::time::Duration::from_millis(10),
+        &MedicOptions::new().check_timeout(std::time::Duration::from_millis(50)),
+    )
+    .unwrap();
+    assert_eq!(result, CheckResult::Fatal);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    assert!(out.contains("Skipped  Never  Deadline exceeded\n"), "{out}");
+}
+
+#[test]
+fn test_medic_deadline_dependencies() {
+    let checks = [
+        Check::new("Config", || Ok((CheckResult::Ok, "Valid".to_string())))
+            .depends_on(&["Installed"]),
+        Check::new("Installed", || {
+            Ok((CheckResult::Error, "Not found".to_string()))
+        }),
+        Check::new("Unknown", || unreachable!()).depends_on(&["No such check"]),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = medic_deadline(
+        &mut out_buf,
+        checks.iter(),
+        std::time::Duration::from_secs(60),
+        &MedicOptions::new(),
+    )
+    .unwrap();
+    assert_eq!(result, CheckResult::Error);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK      MESSAGE
+        Skipped  Config     Skipped because Installed reported Error
+        Error    Installed  Not found
+        Skipped  Unknown    Depends on unknown check No such check\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_expect_eq_check() {
+    const EXPECTED: u32 = 3;
+    let check = crate::checks::expect_eq_check!("abi", 1 + 2, EXPECTED);
+    assert_eq!(check.run().unwrap(), (CheckResult::Ok, "3".to_string()));
+
+    let check = crate::checks::expect_eq_check!("abi", 2, EXPECTED);
+    assert_eq!(
+        check.run().unwrap(),
+        (CheckResult::Error, "Expected 3, found 2".to_string())
+    );
+
+    // Values that aren't `Copy`, with the check run more than once
+    let expected = String::from("v2");
+    let check = crate::checks::expect_eq_check!("schema", "v1".to_string(), expected);
+    for _ in 0..2 {
+        assert_eq!(
+            check.run().unwrap(),
+            (CheckResult::Error, "Expected v2, found v1".to_string())
+        );
+    }
+}
+
+#[test]
+fn test_install_source() {
+    use crate::checks::classify_install_path;
+
+    let classify = |path: &str, cargo_home, target_dir| {
+        classify_install_path(Path::new(path), cargo_home, target_dir)
+    };
+    assert_eq!(
+        classify("/home/user/.cargo/bin/tool", None, None),
+        "cargo install"
+    );
+    assert_eq!(
+        classify("/opt/cargo/bin/tool", Some("/opt/cargo/"), None),
+        "cargo install"
+    );
+    for path in [
+        "/src/tool/target/debug/tool",
+        "/src/tool/target/release/deps/tool-0123abcd",
+        "/src/tool/target/x86_64-unknown-linux-gnu/profiling/tool",
+        "C:\\src\\tool\\target\\release\\tool.exe",
+    ] {
+        assert_eq!(classify(path, None, None), "built from source", "{path}");
+    }
+    assert_eq!(
+        classify("/tmp/build/debug/tool", None, Some("/tmp/build")),
+        "built from source"
+    );
+    assert_eq!(
+        classify("/usr/bin/target", None, None),
+        "system package manager"
+    );
+    assert_eq!(classify("/opt/homebrew/bin/tool", None, None), "Homebrew");
+    assert_eq!(classify("/somewhere/else/tool", None, None), "unknown");
+
+    let (result, message) = crate::checks::CHECK_INSTALL_SOURCE.run().unwrap();
+    assert_eq!(result, CheckResult::Info);
+    assert!(message.contains(" (heuristic, based on "), "{message}");
+}
+
+#[cfg(feature = "fonts")]
+#[test]
+fn test_font_available() {
+    let check = crate::checks::font_available("Medic Test No Such Font Family");
+    assert_eq!(check.name(), "Medic Test No Such Font Family");
+    let (result, message) = check.run().unwrap();
+    assert_eq!(result, CheckResult::Warning);
+    assert!(
+        message.starts_with("Font Medic Test No Such Font Family not found ("),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_translocation() {
+    use crate::checks::is_translocated;
+
+    assert!(is_translocated(Path::new(
+        "/private/var/folders/x1/abc123/T/AppTranslocation/0A1B2C3D-4E5F/d/Tool.app/Contents/MacOS/tool"
+    )));
+    assert!(!is_translocated(Path::new(
+        "/Applications/Tool.app/Contents/MacOS/tool"
+    )));
+    assert!(!is_translocated(Path::new(
+        "/Users/user/NotAppTranslocation/tool"
+    )));
+
+    let (result, message) = crate::checks::CHECK_TRANSLOCATION.run().unwrap();
+    if cfg!(target_os = "macos") {
+        assert_ne!(result, CheckResult::Skipped, "{message}");
+    } else {
+        assert_eq!(result, CheckResult::Skipped, "{message}");
+    }
+}
+
+#[test]
+fn test_medic_stream() {
+    let checks = [Check::new("Check 1", || {
+        Ok((CheckResult::Ok, "All good".to_string()))
+    })];
+    let mut stream = anstream::AutoStream::never(Vec::new());
+
+    let result = medic_stream(&mut stream, checks.iter()).unwrap();
+    assert_eq!(result, CheckResult::Ok);
+
+    let out = String::from_utf8(stream.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT  CHECK    MESSAGE
+        Ok      Check 1  All good\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_health_score() {
+    assert_eq!(health_score([]), 100);
+    assert_eq!(health_score([CheckResult::Ok, CheckResult::Info]), 100);
+    assert_eq!(
+        health_score([CheckResult::Skipped, CheckResult::Warning]),
+        60
+    );
+    assert_eq!(
+        health_score([CheckResult::Ok, CheckResult::Warning, CheckResult::Error]),
+        60
+    );
+    assert_eq!(health_score([CheckResult::Fatal]), 0);
+
+    let weights = HealthWeights {
+        warning: 100,
+        ..HealthWeights::default()
+    };
+    let counts = [CheckResult::Ok, CheckResult::Warning]
+        .into_iter()
+        .collect();
+    assert_eq!(weights.score(&counts), 100);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_readable_tree() {
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Make the directory accessible again, so that it can be removed
+    struct RestorePermissions<'path>(&'path Path);
+
+    impl Drop for RestorePermissions<'_> {
+        fn drop(&mut self) {
+            let _ = std::fs::set_permissions(self.0, std::fs::Permissions::from_mode(0o755));
+        }
+    }
+
+    let root = TempDir::new("readable-tree");
+    let sub = root.path().join("sub");
+    std::fs::create_dir_all(&sub).unwrap();
+    std::fs::write(sub.join("file"), "data").unwrap();
+
+    let check = crate::checks::readable_tree(root.path(), "tree");
+    let (result, message) = check.run().unwrap();
+    assert_eq!(result, CheckResult::Ok);
+    assert!(message.starts_with("All 3 entries"), "{message}");
+
+    let _restore = RestorePermissions(&sub);
+    std::fs::set_permissions(&sub, std::fs::Permissions::from_mode(0o000)).unwrap();
+    // Root can read anything, so only check the failure case when unprivileged
+    if std::fs::read_dir(&sub).is_err() {
+        let (result, message) = check.run().unwrap();
+        assert_eq!(result, CheckResult::Error);
+        assert!(message.contains("sub"), "{message}");
+    }
+}
+
+#[test]
+fn test_fs_checks() {
+    use crate::checks::exists_check;
+    use crate::checks::writable_check;
+
+    let dir = TempDir::new("fs-checks");
+    let root = dir.path();
+    let file = root.join("file");
+    std::fs::write(&file, "data").unwrap();
+    let missing = root.join("missing");
+
+    let (result, message) = exists_check(root, CheckResult::Error, "exists")
+        .run()
+        .unwrap();
+    assert_eq!(result, CheckResult::Ok);
+    assert!(message.starts_with("Directory "), "{message}");
+    let (result, message) = exists_check(&missing, CheckResult::Info, "exists")
+        .run()
+        .unwrap();
+    assert_eq!(result, CheckResult::Info);
+    assert!(message.ends_with("missing does not exist"), "{message}");
+
+    assert_eq!(
+        writable_check(root, "writable").run().unwrap().0,
+        CheckResult::Ok
+    );
+    assert_eq!(
+        writable_check(&file, "writable").run().unwrap().0,
+        CheckResult::Ok
+    );
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "data");
+    assert_eq!(
+        writable_check(&missing, "writable").run().unwrap().0,
+        CheckResult::Error
+    );
+
+    #[cfg(feature = "free-space")]
+    {
+        use crate::checks::free_space_check;
+
+        assert_eq!(
+            free_space_check(root, 0, "space").run().unwrap().0,
+            CheckResult::Ok
+        );
+        let (result, message) = free_space_check(root, u64::MAX, "space").run().unwrap();
+        assert_eq!(result, CheckResult::Warning);
+        assert!(message.contains("(want at least 16384.0 PiB)"), "{message}");
+        assert_eq!(
+            free_space_check(&missing, 0, "space").run().unwrap().0,
+            CheckResult::Error
+        );
+    }
+}
+
+#[test]
+fn test_summary_thresholds() {
+    let render = |worst, threshold| {
+        let mut out_buf = anstream::StripStream::new(Vec::new());
+        summary_with_threshold(&mut out_buf, worst, threshold).unwrap();
+        String::from_utf8(out_buf.into_inner()).unwrap()
+    };
+    let warning_summary =
+        "\nWarning: Warning(s) found, consider investigating (especially if you have issues)\n";
+    assert_eq!(render(CheckResult::Info, CheckResult::Warning), "");
+    assert_eq!(
+        render(CheckResult::Warning, CheckResult::Warning),
+        warning_summary
+    );
+    assert_eq!(render(CheckResult::Warning, CheckResult::Error), "");
+    assert!(render(CheckResult::Fatal, CheckResult::Error).starts_with("\nError: "));
+
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    summary(&mut out_buf, CheckResult::Warning).unwrap();
+    assert_eq!(
+        String::from_utf8(out_buf.into_inner()).unwrap(),
+        warning_summary
+    );
+
+    // The summary can trigger at warning while the exit code only fails on errors
+    assert_eq!(
+        threshold_exit_code(CheckResult::Warning, CheckResult::Error),
+        ExitCode::SUCCESS
+    );
+    assert_eq!(
+        threshold_exit_code(CheckResult::Error, CheckResult::Error),
+        ExitCode::from(2)
+    );
+}
+
+#[test]
+fn test_daemon_health() {
+    use crate::checks::daemon_health;
+    use crate::checks::DaemonAddr;
+    use crate::checks::HealthProbe;
+    use std::io::Read;
+    use std::io::Write;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = DaemonAddr::Tcp(listener.local_addr().unwrap().to_string());
+    let server = std::thread::spawn(move || {
+        for _ in 0..2 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 4];
+            stream.read_exact(&mut buf).unwrap();
+            stream.write_all(b"PONG\n").unwrap();
+        }
+    });
+
+    let probe = |expected: &[u8]| HealthProbe {
+        request: b"PING".to_vec(),
+        expected_response: Some(expected.to_vec()),
+    };
+    let check = daemon_health(addr.clone(), Some(probe(b"PONG")), "daemon");
+    let (result, message) = check.run().unwrap();
+    assert_eq!(result, CheckResult::Ok, "{message}");
+
+    let check = daemon_health(addr, Some(probe(b"HELLO")), "daemon");
+    let (result, message) = check.run().unwrap();
+    assert_eq!(result, CheckResult::Warning, "{message}");
+    assert!(message.ends_with(": PONG"), "{message}");
+    server.join().unwrap();
+
+    // Nothing listens on port 1 of localhost
+    let check = daemon_health(DaemonAddr::Tcp("127.0.0.1:1".to_string()), None, "daemon");
+    assert_eq!(check.run().unwrap().0, CheckResult::Error);
+}
+
+#[test]
+fn test_custom_renderer() {
+    struct CsvRenderer;
+
+    impl RowRenderer for CsvRenderer {
+        fn render_row(
+            &self,
+            outcome: &CheckOutcome,
+            layout: &TableLayout,
+            output: &mut impl std::io::Write,
+        ) -> Result<(), std::io::Error> {
+            let result: &'static str = outcome.result.into();
+            writeln!(
+                output,
+                "{result},{},{},{}",
+                outcome.name,
+                outcome.message,
+                layout.message_column()
+            )
+        }
+    }
+
+    let checks = [
+        Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+        Check::new("Long check name", || Err("Broken".into())),
+    ];
+    let mut out_buf = Vec::new();
+
+    let result = medic_with_renderer(&mut out_buf, checks.iter(), &CsvRenderer).unwrap();
+    assert_eq!(result, CheckResult::Fatal);
+
+    let out = String::from_utf8(out_buf).unwrap();
+    let expected = indoc::indoc! {"
+        Ok,Check 1,All good,25
+        Fatal,Long check name,Broken,25\n"};
+    assert_eq!(out, expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_medic_json() {
+    let checks = [
+        Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+        Check::new("Check 2", || {
+            Ok((CheckResult::Warning, "Not so good\nNot at all".to_string()))
+        }),
+        Check::new("Check 3", || Err("Very bad".into())),
+    ];
+    let mut out_buf = Vec::new();
+
+    let result = crate::medic_json(&mut out_buf, checks.iter()).unwrap();
+    assert_eq!(result, CheckResult::Fatal);
+
+    let out = String::from_utf8(out_buf).unwrap();
+    let expected = indoc::indoc! {r#"
+        {
+          "worst": "fatal",
+          "checks": [
+            {
+              "name": "Check 1",
+              "result": "ok",
+              "message": "All good"
+            },
+            {
+              "name": "Check 2",
+              "result": "warning",
+              "message": "Not so good\nNot at all"
+            },
+            {
+              "name": "Check 3",
+              "result": "fatal",
+              "message": "Very bad"
+            }
+          ]
+        }
+    "#};
+    assert_eq!(out, expected);
+
+    // Rendering pre-computed (here filtered) outcomes
+    let outcomes: Vec<_> = run_checks(checks.iter())
+        .into_iter()
+        .filter(|outcome| outcome.result != CheckResult::Fatal)
+        .collect();
+    let mut out_buf = Vec::new();
+    crate::render_json(&mut out_buf, &outcomes).unwrap();
+    let out: serde_json::Value = serde_json::from_slice(&out_buf).unwrap();
+    assert_eq!(out["worst"], "warning");
+    assert_eq!(out["checks"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_closure_check() {
+    let dir = PathBuf::from("/some/config/dir");
+    let checks = [Check::new("config-dir", move || {
+        Ok((CheckResult::Ok, format!("{}", dir.display())))
+    })];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = medic(&mut out_buf, checks.iter()).unwrap();
+    assert_eq!(result, CheckResult::Ok);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT  CHECK       MESSAGE
+        Ok      config-dir  /some/config/dir\n"};
+    assert_eq!(out, expected);
+    assert_eq!(checks[0].name(), "config-dir");
+}
+
+#[test]
+fn test_stateful_check() {
+    let runs = std::sync::Arc::new(std::sync::Mutex::new(0));
+    let check = Check::new("stateful", {
+        let runs = runs.clone();
+        move || {
+            let mut runs = runs.lock().unwrap();
+            *runs += 1;
+            Ok((CheckResult::Ok, format!("Run {runs}")))
+        }
+    });
+
+    let outcomes = run_checks([&check, &check].into_iter());
+    assert_eq!(outcomes[0].message, "Run 1");
+    assert_eq!(outcomes[1].message, "Run 2");
+    assert_eq!(*runs.lock().unwrap(), 2);
+}
+
+#[test]
+fn test_worst_actionable() {
+    let outcome = |result| CheckOutcome {
+        name: "Check",
+        result,
+        message: String::new(),
+        errored: false,
+        duration: None,
+        hint: None,
+        details: vec![],
+        extended: None,
+    };
+
+    // All informational: highest severity is Info, but there is no problem
+    let all_info = [outcome(CheckResult::Info), outcome(CheckResult::Info)];
+    assert_eq!(worst_result(&all_info), CheckResult::Info);
+    assert_eq!(worst_actionable(&all_info), CheckResult::Ok);
+
+    // Mixed Ok and Info is as nominal as all Ok
+    let mixed = [outcome(CheckResult::Ok), outcome(CheckResult::Info)];
+    assert_eq!(worst_result(&mixed), CheckResult::Info);
+    assert_eq!(worst_actionable(&mixed), CheckResult::Ok);
+    assert_eq!(worst_actionable(&mixed[..1]), CheckResult::Ok);
+
+    // Problems are still reported
+    let warning = [outcome(CheckResult::Info), outcome(CheckResult::Warning)];
+    assert_eq!(worst_actionable(&warning), CheckResult::Warning);
+
+    // Nothing ran
+    assert_eq!(worst_actionable(&[]), CheckResult::Skipped);
+    assert_eq!(
+        worst_actionable(&[outcome(CheckResult::Skipped)]),
+        CheckResult::Skipped
+    );
+}
+
+#[test]
+fn test_run_checks() {
+    let checks = [
+        Check::new("Check 1", || {
+            Ok((CheckResult::Info, "Some info".to_string()))
+        }),
+        Check::new("Check 2", || Err("Broken".into())),
+    ];
+
+    let outcomes = run_checks(checks.iter());
+    assert_eq!(
+        outcomes,
+        [
+            CheckOutcome {
+                name: "Check 1",
+                result: CheckResult::Info,
+                message: "Some info".to_string(),
+                errored: false,
+                duration: None,
+                hint: None,
+                details: vec![],
+                extended: None,
+            },
+            CheckOutcome {
+                name: "Check 2",
+                result: CheckResult::Fatal,
+                message: "Broken".to_string(),
+                errored: true,
+                duration: None,
+                hint: None,
+                details: vec![],
+                extended: None,
+            },
+        ]
+    );
+    assert_eq!(outcomes[0].error(), None);
+    assert_eq!(outcomes[1].error(), Some("Broken"));
+    assert_eq!(worst_result(&outcomes), CheckResult::Fatal);
+    assert_eq!(worst_result(&outcomes[..1]), CheckResult::Info);
+    assert_eq!(worst_result(&[]), CheckResult::Ok);
+
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    render_table(&mut out_buf, &outcomes).unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT  CHECK    MESSAGE
+        Info    Check 1  Some info
+        Fatal   Check 2  Broken\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_medic_markdown() {
+    let checks = [
+        Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+        Check::new("Check 2", || {
+            Ok((CheckResult::Warning, "Not so good\nPipe | here".to_string()))
+        }),
+    ];
+    let mut out_buf = Vec::new();
+
+    let result = medic_markdown(&mut out_buf, checks.iter()).unwrap();
+    assert_eq!(result, CheckResult::Warning);
+    summary_markdown(&mut out_buf, result).unwrap();
+
+    let out = String::from_utf8(out_buf).unwrap();
+    let expected = indoc::indoc! {r"
+        | RESULT | CHECK | MESSAGE |
+        |--------|-------|---------|
+        | Ok | Check 1 | All good |
+        | Warning | Check 2 | Not so good<br>Pipe \| here |
+
+        > **Warning: Warning(s) found, consider investigating (especially if you have issues)**
+    "};
+    assert_eq!(out, expected);
+
+    let outcomes = run_checks(checks.iter());
+    let mut out_buf = Vec::new();
+    render_markdown_details(&mut out_buf, &outcomes, "Doctor output").unwrap();
+    let out = String::from_utf8(out_buf).unwrap();
+    let expected = indoc::indoc! {r"
+        <details>
+        <summary>Doctor output</summary>
+
+        | RESULT | CHECK | MESSAGE |
+        |--------|-------|---------|
+        | Ok | Check 1 | All good |
+        | Warning | Check 2 | Not so good<br>Pipe \| here |
+
+        </details>
+    "};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_medic_filtered() {
+    let checks = [
+        Check::new("A very long name", || {
+            Ok((CheckResult::Ok, "All good".to_string()))
+        }),
+        Check::new("Check 2", || {
+            Ok((CheckResult::Info, "Some info".to_string()))
+        }),
+        Check::new("Check 3", || {
+            Ok((CheckResult::Warning, "Not so good".to_string()))
+        }),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = medic_filtered(&mut out_buf, checks.iter(), CheckResult::Info).unwrap();
+    assert_eq!(result, CheckResult::Warning);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK    MESSAGE
+        Info     Check 2  Some info
+        Warning  Check 3  Not so good\n"};
+    assert_eq!(out, expected);
+
+    // Hidden warnings still count towards the worst level
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    let result = medic_filtered(&mut out_buf, checks.iter(), CheckResult::Error).unwrap();
+    assert_eq!(result, CheckResult::Warning);
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    assert_eq!(out, "RESULT  CHECK  MESSAGE\n");
+}
+
+#[test]
+fn test_quiet() {
+    let ok_checks = [
+        Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+        Check::new("Check 2", || {
+            Ok((CheckResult::Info, "Some info".to_string()))
+        }),
+    ];
+    let options = MedicOptions::new().quiet(true);
+
+    // Nothing at all is shown when there are no problems
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    let result = medic_with_options(&mut out_buf, ok_checks.iter(), &options).unwrap();
+    assert_eq!(result, CheckResult::Info);
+    summary(&mut out_buf, result).unwrap();
+    assert_eq!(String::from_utf8(out_buf.into_inner()).unwrap(), "");
+
+    // But as soon as there is a problem, everything is shown
+    let checks = [
+        Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+        Check::new("Check 3", || {
+            Ok((CheckResult::Warning, "Not so good".to_string()))
+        }),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    let result = medic_with_options(
+        &mut out_buf,
+        checks.iter(),
+        &options.min_level(CheckResult::Warning),
+    )
+    .unwrap();
+    assert_eq!(result, CheckResult::Warning);
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK    MESSAGE
+        Warning  Check 3  Not so good\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_wrapping() {
+    let checks = [
+        Check::new("Check 1", || {
+            Ok((
+                CheckResult::Ok,
+                "The quick brown fox jumps over the lazy dog".to_string(),
+            ))
+        }),
+        Check::new("Check 2", || {
+            Ok((
+                CheckResult::Info,
+                "Short\n/a/very/long/path/without/spaces".to_string(),
+            ))
+        }),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let renderer = TableRenderer::new().with_width(Some(35));
+    medic_with_renderer(&mut out_buf, checks.iter(), &renderer).unwrap();
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT  CHECK    MESSAGE
+        Ok      Check 1  The quick brown
+                         fox jumps over the
+                         lazy dog
+        Info    Check 2  Short
+                         /a/very/long/path/
+                         without/spaces\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_parallel() {
+    let checks = [
+        Check::new("Slow", || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            Ok((CheckResult::Warning, "Took a while".to_string()))
+        }),
+        Check::new("Fast", || Ok((CheckResult::Ok, "Quick".to_string()))),
+        Check::new("Broken", || Err("Very bad".into())),
+    ];
+    assert_eq!(
+        run_checks_parallel(checks.iter()),
+        run_checks(checks.iter())
+    );
+
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    let result = medic_parallel(&mut out_buf, checks.iter()).unwrap();
+    assert_eq!(result, CheckResult::Fatal);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK   MESSAGE
+        Warning  Slow    Took a while
+        Ok       Fast    Quick
+        Fatal    Broken  Very bad\n"};
+    assert_eq!(out, expected);
+
+    // Parallel combined with other options
+    let options = MedicOptions::new().parallel(true).timing(true);
+    let outcomes = run_checks_with(checks.iter(), &options);
+    let names: Vec<_> = outcomes.iter().map(|outcome| outcome.name).collect();
+    assert_eq!(names, ["Slow", "Fast", "Broken"]);
+    assert!(outcomes[0].duration.unwrap() >= std::time::Duration::from_millis(50));
+}
+
+#[test]
+fn test_timing() {
+    let checks = [
+        Check::new("Slow", || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            Ok((CheckResult::Ok, "Took a while".to_string()))
+        }),
+        Check::new("Fast", || Ok((CheckResult::Ok, "Quick".to_string()))),
+        Check::new("Broken", || Err("Very bad".into())),
+    ];
+
+    // Timing is off by default
+    assert!(run_checks(checks.iter())
+        .iter()
+        .all(|outcome| outcome.duration.is_none()));
+
+    let options = MedicOptions::new().slow_threshold(std::time::Duration::from_millis(25));
+    let outcomes = run_checks_with(checks.iter(), &options);
+    assert!(outcomes[0].duration.unwrap() >= std::time::Duration::from_millis(50));
+    assert!(outcomes.iter().all(|outcome| outcome.duration.is_some()));
+    assert_eq!(
+        outcomes
+            .iter()
+            .map(|outcome| outcome.result)
+            .collect::<Vec<_>>(),
+        [CheckResult::Info, CheckResult::Ok, CheckResult::Fatal]
+    );
+    assert!(
+        outcomes[0].message.starts_with("Took a while (slow: "),
+        "{}",
+        outcomes[0].message
+    );
+    assert_eq!(outcomes[1].message, "Quick");
+
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    medic_with_options(
+        &mut out_buf,
+        checks.iter(),
+        &MedicOptions::new().timing(true),
+    )
+    .unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let mut lines = out.lines();
+    assert!(lines.next().unwrap().contains("CHECK   TIME"));
+    assert!(lines.next().unwrap().contains("ms"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_check_in_path() {
+    let (result, message) = crate::checks::check_in_path("sh").run().unwrap();
+    assert_eq!(result, CheckResult::Ok);
+    assert!(message.starts_with("sh found at /"), "{message}");
+
+    let (result, message) = crate::checks::check_in_path("medic-no-such-binary")
+        .run()
+        .unwrap();
+    assert_eq!(result, CheckResult::Warning);
+    assert!(message.contains("not found in PATH"), "{message}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_executable_check() {
+    use crate::checks::executable_check;
+
+    // Tests are run by cargo, so it should be in PATH
+    let check = executable_check(
+        "has-cargo",
+        "cargo",
+        Some(&["--version"]),
+        CheckResult::Error,
+    );
+    assert_eq!(check.name(), "has-cargo");
+    let (result, message) = check.run().unwrap();
+    assert_eq!(result, CheckResult::Ok);
+    assert!(message.contains(". Version: cargo "), "{message}");
+
+    let (result, message) = executable_check("has-sh", "sh", None, CheckResult::Error)
+        .run()
+        .unwrap();
+    assert_eq!(result, CheckResult::Ok);
+    assert!(message.starts_with("sh found at /"), "{message}");
+
+    let (result, message) = executable_check("has-false", "false", Some(&[]), CheckResult::Error)
+        .run()
+        .unwrap();
+    assert_eq!(result, CheckResult::Warning);
+    assert!(message.contains("failed to get version"), "{message}");
+
+    let (result, message) = executable_check(
+        "optional",
+        "medic-no-such-binary",
+        Some(&["--version"]),
+        CheckResult::Info,
+    )
+    .run()
+    .unwrap();
+    assert_eq!(result, CheckResult::Info);
+    assert_eq!(message, "medic-no-such-binary not found in PATH");
+}
+
+/// Minimal executor for testing async code without depending on a runtime
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    struct ThreadWaker(std::thread::Thread);
+
+    impl std::task::Wake for ThreadWaker {
+        fn wake(self: std::sync::Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut future = std::pin::pin!(future);
+    let waker = std::sync::Arc::new(ThreadWaker(std::thread::current())).into();
+    let mut cx = std::task::Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            std::task::Poll::Ready(output) => return output,
+            std::task::Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_medic_async() {
+    use crate::AsyncCheck;
+
+    let checks = [
+        AsyncCheck::new("Check 1", || async {
+            Ok((CheckResult::Ok, "All good".to_string()))
+        })
+        .with_hint("Not shown as there is no problem"),
+        AsyncCheck::new("Check 2", || async { Err("Unreachable".into()) })
+            .with_hint("Check the network"),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = block_on(crate::medic_async(&mut out_buf, checks.iter())).unwrap();
+    assert_eq!(result, CheckResult::Fatal);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT  CHECK    MESSAGE
+        Ok      Check 1  All good
+        Fatal   Check 2  Unreachable
+                         Hint: Check the network\n"};
+    assert_eq!(out, expected);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_medic_async_concurrent() {
+    use crate::AsyncCheck;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    /// Future that is pending until the flag is set
+    struct WaitFor(Arc<AtomicBool>);
+
+    impl std::future::Future for WaitFor {
+        type Output = ();
+
+        fn poll(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<()> {
+            if self.0.load(Ordering::SeqCst) {
+                std::task::Poll::Ready(())
+            } else {
+                cx.waker().wake_by_ref();
+                std::task::Poll::Pending
+            }
+        }
+    }
+
+    // The first check can only complete after the second one has run, which
+    // would hang if they were run one at a time
+    let flag = Arc::new(AtomicBool::new(false));
+    let checks = [
+        AsyncCheck::new("Waiting", {
+            let flag = flag.clone();
+            move || {
+                let flag = flag.clone();
+                async move {
+                    WaitFor(flag).await;
+                    Ok((CheckResult::Ok, "Done waiting".to_string()))
+                }
+            }
+        }),
+        AsyncCheck::new("Signal", move || {
+            let flag = flag.clone();
+            async move {
+                flag.store(true, Ordering::SeqCst);
+                Ok((CheckResult::Warning, "Signalled".to_string()))
+            }
+        }),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = block_on(crate::medic_async_concurrent(&mut out_buf, checks.iter())).unwrap();
+    assert_eq!(result, CheckResult::Warning);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK    MESSAGE
+        Ok       Waiting  Done waiting
+        Warning  Signal   Signalled\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_check_env_vars() {
+    use crate::checks::report_env_vars;
+    use crate::checks::DEFAULT_REDACT_PATTERNS;
+
+    // The environment is shared between tests (running in parallel), so use
+    // fixed values instead
+    let lookup = |name: &str| match name {
+        "MEDIC_TEST_PLAIN" => Some("visible".to_string()),
+        "MEDIC_TEST_API_Token" => Some("hunter2".to_string()),
+        _ => None,
+    };
+    assert_eq!(
+        report_env_vars(
+            &[
+                "MEDIC_TEST_PLAIN",
+                "MEDIC_TEST_API_Token",
+                "MEDIC_TEST_UNSET"
+            ],
+            DEFAULT_REDACT_PATTERNS,
+            lookup
+        ),
+        (
+            CheckResult::Info,
+            "MEDIC_TEST_PLAIN=visible\nMEDIC_TEST_API_Token=<redacted, len=7>\nMEDIC_TEST_UNSET \
+             not set"
+                .to_string()
+        )
+    );
+
+    assert_eq!(
+        report_env_vars(&["MEDIC_TEST_PLAIN"], &["MEDIC_*_PLAIN"], lookup),
+        (
+            CheckResult::Ok,
+            "MEDIC_TEST_PLAIN=<redacted, len=7>".to_string()
+        )
+    );
+
+    // Reading the real environment (`PATH` is virtually always set)
+    let check = crate::checks::check_env_vars_redacting(&["PATH"], &["PA*"]);
+    let (result, message) = check.run().unwrap();
+    assert_eq!(result, CheckResult::Ok);
+    assert!(message.starts_with("PATH=<redacted, len="), "{message}");
+}
+
+#[test]
+fn test_env_var_check() {
+    use crate::checks::env_var_check;
+    use crate::checks::evaluate_env_var;
+    use crate::checks::EnvExpectation;
+    use crate::checks::DEFAULT_REDACT_PATTERNS;
+
+    // The environment is shared between tests (running in parallel), so use
+    // fixed values instead
+    let run = |var, expectation| {
+        let value = match var {
+            "MEDIC_TEST_CHECK_MODE" => Some("fast"),
+            "MEDIC_TEST_CHECK_SECRET" => Some("hunter2"),
+            _ => None,
+        };
+        evaluate_env_var(
+            var,
+            value,
+            expectation,
+            CheckResult::Error,
+            CheckResult::Warning,
+            DEFAULT_REDACT_PATTERNS,
+        )
+    };
+
+    assert_eq!(
+        run("MEDIC_TEST_CHECK_UNSET", EnvExpectation::Unset),
+        (
+            CheckResult::Ok,
+            "MEDIC_TEST_CHECK_UNSET is not set".to_string()
+        )
+    );
+    assert_eq!(
+        run("MEDIC_TEST_CHECK_SECRET", EnvExpectation::Unset),
+        (
+            CheckResult::Warning,
+            "MEDIC_TEST_CHECK_SECRET=<redacted, len=7> (expected it to not be set)".to_string()
+        )
+    );
+    assert_eq!(
+        run("MEDIC_TEST_CHECK_UNSET", EnvExpectation::Set),
+        (
+            CheckResult::Error,
+            "MEDIC_TEST_CHECK_UNSET is not set".to_string()
+        )
+    );
+    assert_eq!(
+        run(
+            "MEDIC_TEST_CHECK_UNSET",
+            EnvExpectation::OneOf(&["fast", "slow"])
+        ),
+        (
+            CheckResult::Error,
+            "MEDIC_TEST_CHECK_UNSET is not set (expected one of: fast, slow)".to_string()
+        )
+    );
+    assert_eq!(
+        run(
+            "MEDIC_TEST_CHECK_MODE",
+            EnvExpectation::OneOf(&["fast", "slow"])
+        ),
+        (CheckResult::Ok, "MEDIC_TEST_CHECK_MODE=fast".to_string())
+    );
+    assert_eq!(
+        run("MEDIC_TEST_CHECK_MODE", EnvExpectation::OneOf(&["slow"])),
+        (
+            CheckResult::Warning,
+            "MEDIC_TEST_CHECK_MODE=fast (expected one of: slow)".to_string()
+        )
+    );
+    let short = EnvExpectation::Predicate {
+        test: |value| value.len() < 4,
+        description: "at most 3 characters",
+    };
+    assert_eq!(
+        run("MEDIC_TEST_CHECK_MODE", short),
+        (
+            CheckResult::Warning,
+            "MEDIC_TEST_CHECK_MODE=fast (expected at most 3 characters)".to_string()
+        )
+    );
+
+    // Redaction patterns are configurable
+    assert_eq!(
+        evaluate_env_var(
+            "MEDIC_TEST_CHECK_MODE",
+            Some("fast"),
+            EnvExpectation::Set,
+            CheckResult::Error,
+            CheckResult::Warning,
+            &["*_MODE"],
+        ),
+        (
+            CheckResult::Ok,
+            "MEDIC_TEST_CHECK_MODE=<redacted, len=4>".to_string()
+        )
+    );
+
+    // Reading the real environment (nothing sets this variable)
+    let check = env_var_check(
+        "env",
+        "MEDIC_TEST_CHECK_UNSET",
+        EnvExpectation::Set,
+        CheckResult::Fatal,
+        CheckResult::Warning,
+        DEFAULT_REDACT_PATTERNS,
+    );
+    assert_eq!(check.run().unwrap().0, CheckResult::Fatal);
+}
+
+#[test]
+fn test_summary_config() {
+    let render = |worst, config: &SummaryConfig| {
+        let mut out_buf = Vec::new();
+        summary_with_config(&mut out_buf, worst, config).unwrap();
+        String::from_utf8(out_buf).unwrap()
+    };
+
+    // The default must match the exact historical output, including colours
+    let mut out_buf = Vec::new();
+    summary(&mut out_buf, CheckResult::Fatal).unwrap();
+    assert_eq!(
+        String::from_utf8(out_buf).unwrap(),
+        "\n\x1b[31mError\x1b[0m: Error(s) found, you should rectify these for proper operation\n"
+    );
+    assert_eq!(
+        render(CheckResult::Warning, &SummaryConfig::default()),
+        "\n\x1b[33mWarning\x1b[0m: Warning(s) found, consider investigating (especially if you \
+         have issues)\n"
+    );
+    assert_eq!(render(CheckResult::Info, &SummaryConfig::default()), "");
+
+    let config = SummaryConfig::new()
+        .threshold(CheckResult::Info)
+        .message(CheckResult::Info, "Hinweis", "Nur Informationen")
+        .message(CheckResult::Warning, "Warnung", "Muss behoben werden");
+    assert_eq!(
+        render(CheckResult::Info, &config),
+        "\n\x1b[32mHinweis\x1b[0m: Nur Informationen\n"
+    );
+    assert_eq!(
+        render(CheckResult::Warning, &config),
+        "\n\x1b[33mWarnung\x1b[0m: Muss behoben werden\n"
+    );
+    assert_eq!(render(CheckResult::Ok, &config), "");
+
+    let config = SummaryConfig::new().clear_message(CheckResult::Error);
+    assert!(render(CheckResult::Error, &config).contains("Warning(s) found"));
+}
+
+#[test]
+fn test_medic_report() {
+    let suffix = String::from("captured");
+    let mut report = MedicReport::new();
+    report
+        .add(Check::new("Check 1", move || {
+            Ok((CheckResult::Ok, format!("All good, {suffix}")))
+        }))
+        .add_all([Check::new("Check 2", || {
+            Ok((CheckResult::Warning, "Not so good".to_string()))
+        })]);
+    assert_eq!(report.checks().len(), 2);
+
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    let result = report.run_summary(&mut out_buf).unwrap();
+    assert_eq!(result, CheckResult::Warning);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK    MESSAGE
+        Ok       Check 1  All good, captured
+        Warning  Check 2  Not so good
+
+        Warning: Warning(s) found, consider investigating (especially if you have issues)\n"};
+    assert_eq!(out, expected);
+
+    report.add(Check::new("Check 1", || {
+        Ok((CheckResult::Ok, String::new()))
+    }));
+    let mut out_buf = Vec::new();
+    match report.run(&mut out_buf).unwrap_err() {
+        MedicError::DuplicateChecks(names) => assert_eq!(names, ["Check 1"]),
+        err => panic!("Unexpected error: {err}"),
+    }
+    assert!(out_buf.is_empty());
+}
+
+#[test]
+fn test_medic_report_format() {
+    let mut out_buf = Vec::new();
+    let outcomes = MedicReport::new()
+        .add_all([
+            Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+            Check::new("Check 2", || {
+                Ok((CheckResult::Warning, "Not so good".to_string()))
+            }),
+        ])
+        .format(OutputFormat::Markdown)
+        .min_severity(CheckResult::Warning)
+        .run_outcomes(&mut out_buf)
+        .unwrap();
+
+    // All outcomes are returned, even those not shown
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(worst_result(&outcomes), CheckResult::Warning);
+    let out = String::from_utf8(out_buf).unwrap();
+    let expected = indoc::indoc! {"
+        | RESULT | CHECK | MESSAGE |
+        |--------|-------|---------|
+        | Warning | Check 2 | Not so good |
+    "};
+    assert_eq!(out, expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_medic_report_json() {
+    let mut report = MedicReport::new();
+    report
+        .add_all([
+            Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+            Check::new("Check 2", || {
+                Ok((CheckResult::Warning, "Not so good".to_string()))
+            }),
+        ])
+        .format(OutputFormat::Json);
+
+    // Hidden outcomes still count towards the worst result
+    let mut out_buf = Vec::new();
+    report
+        .min_severity(CheckResult::Error)
+        .run(&mut out_buf)
+        .unwrap();
+    let out: serde_json::Value = serde_json::from_slice(&out_buf).unwrap();
+    assert_eq!(out["worst"], "warning");
+    assert_eq!(out["checks"], serde_json::json!([]));
+
+    // Quiet mode still writes a valid document when there is nothing to show
+    let mut out_buf = Vec::new();
+    MedicReport::new()
+        .add(Check::new("Check 1", || {
+            Ok((CheckResult::Ok, "All good".to_string()))
+        }))
+        .options(MedicOptions::new().quiet(true))
+        .format(OutputFormat::Json)
+        .run(&mut out_buf)
+        .unwrap();
+    let out: serde_json::Value = serde_json::from_slice(&out_buf).unwrap();
+    assert_eq!(out["worst"], "ok");
+    assert_eq!(out["checks"], serde_json::json!([]));
+}
+
+#[test]
+fn test_medic_report_color() {
+    let mut report = MedicReport::new();
+    report.add(Check::new("Check 1", || {
+        Ok((CheckResult::Warning, "Not so good".to_string()))
+    }));
+
+    let mut out_buf = Vec::new();
+    report
+        .color(anstream::ColorChoice::Never)
+        .run_summary(&mut out_buf)
+        .unwrap();
+    let out = String::from_utf8(out_buf).unwrap();
+    assert!(!out.contains('\x1b'), "{out:?}");
+    assert!(out.starts_with("RESULT   CHECK    MESSAGE\n"), "{out:?}");
+
+    let mut out_buf = Vec::new();
+    report
+        .color(anstream::ColorChoice::Always)
+        .run_summary(&mut out_buf)
+        .unwrap();
+    let out = String::from_utf8(out_buf).unwrap();
+    assert!(out.contains("\x1b[33mWarning\x1b[0m"), "{out:?}");
+}
+
+#[test]
+fn test_exit_code() {
+    assert_eq!(CheckResult::Skipped.exit_code(), 0);
+    assert_eq!(CheckResult::Ok.exit_code(), 0);
+    assert_eq!(CheckResult::Info.exit_code(), 0);
+    assert_eq!(CheckResult::Warning.exit_code(), 1);
+    assert_eq!(CheckResult::Error.exit_code(), 2);
+    assert_eq!(CheckResult::Fatal.exit_code(), 2);
+    assert_eq!(
+        threshold_exit_code(CheckResult::Fatal, CheckResult::Error),
+        ExitCode::from(2)
+    );
+    assert_eq!(
+        threshold_exit_code(CheckResult::Warning, CheckResult::Warning),
+        ExitCode::from(1)
+    );
+    assert_eq!(
+        threshold_exit_code(CheckResult::Info, CheckResult::Info),
+        ExitCode::from(1)
+    );
+    assert_eq!(
+        threshold_exit_code(CheckResult::Info, CheckResult::Warning),
+        ExitCode::SUCCESS
+    );
+
+    assert_eq!(
+        CheckResult::Warning.to_exit_code(ExitPolicy::FailOnWarning),
+        ExitCode::from(1)
+    );
+    assert_eq!(
+        CheckResult::Warning.to_exit_code(ExitPolicy::FailOnError),
+        ExitCode::SUCCESS
+    );
+    assert_eq!(
+        CheckResult::Fatal.to_exit_code(ExitPolicy::FailOnError),
+        ExitCode::from(2)
+    );
+    assert_eq!(
+        CheckResult::Fatal.to_exit_code(ExitPolicy::Never),
+        ExitCode::SUCCESS
+    );
+}
+
+#[test]
+fn test_medic_grouped() {
+    let groups = [
+        CheckGroup::new(
+            "installation",
+            [
+                Check::new("Check 1", || Ok((CheckResult::Ok, "All good".to_string()))),
+                Check::new("Check 2", || {
+                    Ok((CheckResult::Info, "Some info".to_string()))
+                }),
+            ],
+        ),
+        CheckGroup::new(
+            "network",
+            [Check::new("Long check name", || {
+                Ok((CheckResult::Warning, "Not so good".to_string()))
+            })],
+        ),
+    ];
+    assert_eq!(groups[1].name(), "network");
+    assert_eq!(groups[0].checks().len(), 2);
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = medic_grouped(&mut out_buf, groups.iter()).unwrap();
+    assert_eq!(result, CheckResult::Warning);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK            MESSAGE
+        installation: Info
+        Ok       Check 1          All good
+        Info     Check 2          Some info
+
+        network: Warning
+        Warning  Long check name  Not so good\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_medic_grouped_dependencies() {
+    let groups = [
+        CheckGroup::new(
+            "config",
+            [Check::new("Config", || unreachable!()).depends_on(&["Installed"])],
+        ),
+        CheckGroup::new(
+            "installation",
+            [Check::new("Installed", || {
+                Ok((CheckResult::Warning, "Outdated".to_string()))
+            })],
+        ),
+        CheckGroup::new("empty", []),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+
+    let result = medic_grouped(&mut out_buf, groups.iter()).unwrap();
+    assert_eq!(result, CheckResult::Warning);
+
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK      MESSAGE
+        config: Ok
+        Skipped  Config     Skipped because Installed reported Warning
+
+        installation: Warning
+        Warning  Installed  Outdated
+
+        empty: Ok\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_check_timeout() {
+    let checks = [
+        Check::new("Fast", || Ok((CheckResult::Ok, "All good".to_string()))),
+        Check::new("Hung", || {
+            std::thread::sleep(std::time::Duration::from_secs(60));
+            Ok((CheckResult::Ok, "Finally".to_string()))
+        }),
+        Check::new("Broken", || Err("Very bad".into())),
+    ];
+    let options = MedicOptions::new().check_timeout(std::time::Duration::from_millis(100));
+
+    let start = std::time::Instant::now();
+    let outcomes = run_checks_with(checks.iter(), &options);
+    assert!(start.elapsed() < std::time::Duration::from_secs(30));
+
+    assert_eq!(outcomes[0].result, CheckResult::Ok);
+    assert_eq!(outcomes[0].message, "All good");
+    assert_eq!(outcomes[1].result, CheckResult::Fatal);
+    assert_eq!(outcomes[1].error(), Some("Timed out after 100ms"));
+    assert_eq!(outcomes[2].result, CheckResult::Fatal);
+    assert_eq!(outcomes[2].error(), Some("Very bad"));
+}
+
+#[test]
+fn test_on_error() {
+    const CHECK_OPTIONAL: Check =
+        Check::new_const("Optional", || Err("Not installed".into())).on_error(CheckResult::Info);
+    let checks = [
+        CHECK_OPTIONAL,
+        Check::new("Required", || Err("Not installed".into())),
+    ];
+
+    let outcomes = run_checks(checks.iter());
+    assert_eq!(outcomes[0].result, CheckResult::Info);
+    assert_eq!(outcomes[0].error(), Some("Not installed"));
+    assert_eq!(outcomes[1].result, CheckResult::Fatal);
+    assert_eq!(worst_result(&outcomes[..1]), CheckResult::Info);
+}
+
+#[test]
+fn test_hint() {
+    let checks = [
+        Check::new("Config", || {
+            Ok((CheckResult::Error, "Config file missing".to_string()))
+        })
+        .with_hint("Run `mytool init` to create it"),
+        Check::new("Fine", || Ok((CheckResult::Info, "All good".to_string())))
+            .with_hint("Not shown as there is no problem"),
+        Check::new_detailed("Registry", || {
+            Ok(
+                DetailedResult::new(CheckResult::Warning, "Registry is stale")
+                    .hint(format!("Run `mytool update` (last updated {} days ago)", 3)),
+            )
+        })
+        .with_hint("Overridden by the check"),
+        Check::new_detailed("Cache", || {
+            Ok(DetailedResult::new(CheckResult::Ok, "Cache is fresh").hint("Not shown either"))
+        }),
+    ];
+
+    let outcomes = run_checks(checks.iter());
+    assert_eq!(
+        outcomes[2].hint.as_deref(),
+        Some("Run `mytool update` (last updated 3 days ago)")
+    );
+    assert_eq!(outcomes[3].hint, None);
+    assert_eq!(
+        outcomes[0].hint.as_deref(),
+        Some("Run `mytool init` to create it")
+    );
+    assert_eq!(outcomes[1].hint, None);
+
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    render_table(&mut out_buf, &outcomes[..2]).unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT  CHECK   MESSAGE
+        Error   Config  Config file missing
+                        Hint: Run `mytool init` to create it
+        Info    Fine    All good\n"};
+    assert_eq!(out, expected);
+
+    let mut out_buf = Vec::new();
+    render_markdown_details(&mut out_buf, &outcomes[..1], "Output").unwrap();
+    let out = String::from_utf8(out_buf).unwrap();
+    assert!(
+        out.contains(
+            "| Error | Config | Config file missing<br>Hint: Run `mytool init` to create it |"
+        ),
+        "{out}"
+    );
+}
+
+#[test]
+fn test_medic_fix() {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    let fixed = Arc::new(AtomicBool::new(false));
+    let checks = [
+        Check::new("Config", {
+            let fixed = fixed.clone();
+            move || {
+                Ok(if fixed.load(Ordering::SeqCst) {
+                    (CheckResult::Ok, "Config file found".to_string())
+                } else {
+                    (CheckResult::Error, "Config file missing".to_string())
+                })
+            }
+        })
+        .with_fix({
+            let fixed = fixed.clone();
+            move || {
+                fixed.store(true, Ordering::SeqCst);
+                Ok("Created config file".to_string())
+            }
+        }),
+        Check::new("Cache", || {
+            Ok((CheckResult::Warning, "Cache is stale".to_string()))
+        })
+        .with_fix(|| Err("Permission denied".into())),
+        Check::new("Other", || {
+            Ok((CheckResult::Warning, "No fix available".to_string()))
+        }),
+    ];
+
+    // Dry run doesn't change anything
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    let result = medic_fix(&mut out_buf, checks.iter(), true, |_| unreachable!()).unwrap();
+    assert_eq!(result, CheckResult::Error);
+    assert!(!fixed.load(Ordering::SeqCst));
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK   MESSAGE
+        Error    Config  Config file missing
+        Warning  Cache   Cache is stale
+        Warning  Other   No fix available
+
+        Would fix Config
+        Would fix Cache\n"};
+    assert_eq!(out, expected);
+
+    // Declining a fix
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    let result = medic_fix(&mut out_buf, checks.iter(), false, |outcome| {
+        outcome.name == "Config"
+    })
+    .unwrap();
+    assert_eq!(result, CheckResult::Warning);
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK   MESSAGE
+        Error    Config  Config file missing
+        Warning  Cache   Cache is stale
+        Warning  Other   No fix available
+
+        Fixed Config: Created config file
+        Not fixing Cache (declined)
+
+        RESULT   CHECK   MESSAGE
+        Ok       Config  Config file found
+        Warning  Cache   Cache is stale
+        Warning  Other   No fix available\n"};
+    assert_eq!(out, expected);
+
+    // Failing fix
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    medic_fix(&mut out_buf, checks.iter(), false, |_| true).unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    assert!(
+        out.contains("\nFailed to fix Cache: Permission denied\n"),
+        "{out}"
+    );
+    assert!(!out.contains("Fixed Config"), "{out}");
+}
+
+#[test]
+fn test_redaction() {
+    use crate::redact::replace_path;
+    use crate::redact::replace_word;
+
+    let rules = RedactionRules::new().username(false);
+    if let Ok(home) = std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }) {
+        if home.len() > 1 {
+            let message = format!("Config at {home}/.config/app.toml");
+            assert_eq!(rules.apply(&message), "Config at ~/.config/app.toml");
+            assert_eq!(rules.home(false).apply(&message), message);
+        }
+    }
+
+    // User names are only replaced as whole words
+    assert_eq!(
+        replace_word("alice ran /home/alice/bin as alice_2", "alice", "<user>"),
+        "<user> ran /home/<user>/bin as alice_2"
+    );
+    assert_eq!(
+        replace_word("malice alicé", "alice", "<user>"),
+        "malice alicé"
+    );
+    assert_eq!(replace_word("bob", "", "<user>"), "bob");
+
+    // Paths are only replaced if they aren't a prefix of another path
+    assert_eq!(
+        replace_path(
+            "/home/alice/x, /home/alice2/x, /home/alice-old and /home/alice.",
+            "/home/alice",
+            "~"
+        ),
+        "~/x, /home/alice2/x, /home/alice-old and ~."
+    );
+    assert_eq!(
+        replace_path("/home/alice.bak", "/home/alice", "~"),
+        "/home/alice.bak"
+    );
+    assert_eq!(
+        replace_path(r"C:\Users\alice\x", r"C:\Users\alice", "~"),
+        r"~\x"
+    );
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_redaction_patterns() {
+    let rules = RedactionRules::new()
+        .username(false)
+        .pattern(regex::Regex::new(r"token=\w+").unwrap(), "token=<redacted>")
+        .pattern(regex::Regex::new(r"host (\w+)").unwrap(), "host <$1>");
+    assert_eq!(
+        rules.apply("Using token=abc123 on host example"),
+        "Using token=<redacted> on host <example>"
+    );
+
+    // Applied to everything from the check when running checks
+    let checks = [Check::new_detailed("Check 1", || {
+        Ok(DetailedResult::new(CheckResult::Error, "token=hunter2")
+            .detail("config", "token=hunter2")
+            .hint("Rotate token=hunter2"))
+    })];
+    let options = MedicOptions::new().redact(rules);
+    let outcomes = run_checks_with(checks.iter(), &options);
+    assert_eq!(outcomes[0].message, "token=<redacted>");
+    assert_eq!(outcomes[0].details[0].1, "token=<redacted>");
+    assert_eq!(outcomes[0].hint.as_deref(), Some("Rotate token=<redacted>"));
+
+    // And to asynchronous checks
+    #[cfg(feature = "async")]
+    {
+        let checks = [crate::AsyncCheck::new("Check 2", || async {
+            Ok((CheckResult::Ok, "token=hunter2".to_string()))
+        })];
+        for parallel in [false, true] {
+            let options = options.clone().parallel(parallel);
+            let outcomes = block_on(crate::run_checks_async_with(checks.iter(), &options));
+            assert_eq!(outcomes[0].message, "token=<redacted>");
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_check_locale() {
+    use crate::checks::is_utf8;
+    use crate::checks::lossy_vars;
+    use crate::checks::report_locale;
+    use std::os::unix::ffi::OsStringExt;
+
+    assert!(is_utf8("en_US.UTF-8"));
+    assert!(is_utf8("en_US.utf8@euro"));
+    assert!(!is_utf8("C"));
+    assert!(!is_utf8("de_DE.ISO-8859-1"));
+    assert!(!is_utf8("en_US@utf8"));
+
+    // The environment is shared between tests (running in parallel), so use
+    // fixed values instead
+    let vars = |vars: &[(&str, &str)]| {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<Vec<_>>()
+    };
+    let (result, message) = report_locale(
+        vars(&[
+            ("LANG", "en_US.UTF-8"),
+            ("LC_ALL", "C"),
+            ("TERM", "xterm"),
+            ("HOME", "/home/user"),
+        ]),
+        Some(80),
+    );
+    assert_eq!(
+        message,
+        "LANG=en_US.UTF-8\nLC_ALL=C\nTERM=xterm\nTerminal width: 80\nLocale C is not UTF-8, output \
+         may be garbled"
+    );
+    assert_eq!(result, CheckResult::Warning);
+
+    let (result, message) = report_locale(vars(&[("LC_ALL", ""), ("LANG", "C.utf8")]), None);
+    assert_eq!(result, CheckResult::Ok);
+    assert_eq!(
+        message,
+        "LANG=C.utf8\nLC_ALL=\nTERM not set\nstdout is not a terminal"
+    );
+
+    let (result, message) = report_locale(vec![], None);
+    assert_eq!(result, CheckResult::Warning);
+    assert!(message.ends_with("No locale set (defaults to C), output may be garbled"));
+
+    // Invalid Unicode in some unrelated variable must not break the check
+    let vars = [
+        (
+            std::ffi::OsString::from("BAD"),
+            std::ffi::OsString::from_vec(vec![0xff]),
+        ),
+        ("LANG".into(), "en_US.UTF-8".into()),
+    ];
+    let (result, message) = report_locale(lossy_vars(vars), None);
+    assert_eq!(result, CheckResult::Ok);
+    assert!(message.starts_with("LANG=en_US.UTF-8\n"), "{message}");
+
+    // The real environment
+    let (_, message) = crate::checks::CHECK_LOCALE.run().unwrap();
+    assert!(message.contains("TERM"), "{message}");
+}
+
+#[test]
+fn test_dependencies() {
+    let checks = [
+        // Dependencies may be declared before what they depend on
+        Check::new("Config", || Ok((CheckResult::Ok, "Valid".to_string())))
+            .depends_on(&["Installed"]),
+        Check::new("Installed", || Ok((CheckResult::Info, "Found".to_string()))),
+        Check::new("Missing", || {
+            Ok((CheckResult::Warning, "Not found".to_string()))
+        }),
+        Check::new("Uses missing", || unreachable!()).depends_on(&["Installed", "Missing"]),
+        Check::new("Transitive", || unreachable!()).depends_on(&["Uses missing"]),
+        Check::new("Unknown", || unreachable!()).depends_on(&["No such check"]),
+        Check::new("Cycle 1", || unreachable!()).depends_on(&["Cycle 2"]),
+        Check::new("Cycle 2", || unreachable!()).depends_on(&["Cycle 1"]),
+    ];
+
+    for options in [MedicOptions::new(), MedicOptions::new().parallel(true)] {
+        let mut out_buf = anstream::StripStream::new(Vec::new());
+        let outcomes = run_checks_with(checks.iter(), &options);
+        render_table(&mut out_buf, &outcomes).unwrap();
+        let out = String::from_utf8(out_buf.into_inner()).unwrap();
+        let expected = indoc::indoc! {"
+            RESULT   CHECK         MESSAGE
+            Ok       Config        Valid
+            Info     Installed     Found
+            Warning  Missing       Not found
+            Skipped  Uses missing  Skipped because Missing reported Warning
+            Skipped  Transitive    Skipped because Uses missing reported Skipped
+            Skipped  Unknown       Depends on unknown check No such check
+            Skipped  Cycle 1       Circular dependency
+            Skipped  Cycle 2       Circular dependency\n"};
+        assert_eq!(out, expected);
+    }
+}
+
+#[test]
+fn test_medic_incremental() {
+    /// Writer recording how many checks had finished at each write
+    struct Recorder {
+        done: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        writes: Vec<(usize, Vec<u8>)>,
+    }
+
+    impl std::io::Write for Recorder {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let done = self.done.load(std::sync::atomic::Ordering::SeqCst);
+            self.writes.push((done, buf.to_vec()));
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let done = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let make_check = |name, result| {
+        let done = done.clone();
+        Check::new(name, move || {
+            done.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok((result, "Message".to_string()))
+        })
+    };
+    let checks = [
+        make_check("Check 1", CheckResult::Ok),
+        make_check("Long check name", CheckResult::Warning),
+    ];
+    let mut recorder = Recorder {
+        done: done.clone(),
+        writes: vec![],
+    };
+
+    let result = medic_incremental(&mut recorder, checks.iter(), &MedicOptions::new()).unwrap();
+    assert_eq!(result, CheckResult::Warning);
+
+    // The first row was written before the second check ran
+    let first_row = recorder
+        .writes
+        .iter()
+        .position(|(_, buf)| String::from_utf8_lossy(buf).contains("Check 1"))
+        .unwrap();
+    assert_eq!(recorder.writes[first_row].0, 1);
+
+    let out: Vec<u8> = recorder
+        .writes
+        .into_iter()
+        .flat_map(|(_, buf)| buf)
+        .collect();
+    let mut stripped = anstream::StripStream::new(Vec::new());
+    std::io::Write::write_all(&mut stripped, &out).unwrap();
+    let out = String::from_utf8(stripped.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK            MESSAGE
+        Ok       Check 1          Message
+        Warning  Long check name  Message\n"};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_detailed_check() {
+    let checks = [
+        Check::new_detailed("chezmoi", || {
+            Ok(
+                DetailedResult::new(CheckResult::Warning, "Chezmoi is outdated")
+                    .detail("path", "/usr/bin/chezmoi")
+                    .detail("version", "2.40.0"),
+            )
+        })
+        .with_hint("Upgrade chezmoi"),
+        Check::new("Plain", || Ok((CheckResult::Ok, "No details".to_string()))),
+    ];
+
+    let outcomes = run_checks(checks.iter());
+    assert_eq!(
+        outcomes[0].details,
+        vec![
+            ("path".to_string(), "/usr/bin/chezmoi".to_string()),
+            ("version".to_string(), "2.40.0".to_string())
+        ]
+    );
+    assert!(outcomes[1].details.is_empty());
+
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    render_table(&mut out_buf, &outcomes).unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK    MESSAGE
+        Warning  chezmoi  Chezmoi is outdated
+                          path:     /usr/bin/chezmoi
+                          version:  2.40.0
+                          Hint: Upgrade chezmoi
+        Ok       Plain    No details\n"};
+    assert_eq!(out, expected);
+
+    let mut out_buf = Vec::new();
+    render_markdown_details(&mut out_buf, &outcomes[..1], "Output").unwrap();
+    let out = String::from_utf8(out_buf).unwrap();
+    assert!(
+        out.contains(
+            "| Warning | chezmoi | Chezmoi is outdated<br>path: /usr/bin/chezmoi\
+             <br>version: 2.40.0<br>Hint: Upgrade chezmoi |"
+        ),
+        "{out}"
+    );
+
+    #[cfg(feature = "serde")]
+    {
+        let mut out_buf = Vec::new();
+        crate::render_json(&mut out_buf, &outcomes).unwrap();
+        let out: serde_json::Value = serde_json::from_slice(&out_buf).unwrap();
+        assert_eq!(out["checks"][0]["details"]["version"], "2.40.0");
+        assert!(out["checks"][1].get("details").is_none());
+    }
+}
+
+#[cfg(feature = "macros")]
+#[crate::check(hint = "Set up the registry")]
+fn registered_check() -> Result<(CheckResult, String), Box<dyn std::error::Error + Send + Sync>> {
+    Ok((CheckResult::Warning, "Registered".to_string()))
+}
+
+#[cfg(feature = "macros")]
+#[crate::check(name = "another-registered")]
+fn another() -> Result<(CheckResult, String), Box<dyn std::error::Error + Send + Sync>> {
+    Ok((CheckResult::Ok, "Also registered".to_string()))
+}
+
+#[cfg(feature = "macros")]
+#[test]
+fn test_registered_checks() {
+    let checks = crate::registered_checks();
+    let names: Vec<_> = checks.iter().map(|check| check.name()).collect();
+    assert_eq!(names, ["another-registered", "registered-check"]);
+
+    let outcomes = run_checks(checks.into_iter());
+    assert_eq!(outcomes[1].result, CheckResult::Warning);
+    assert_eq!(outcomes[1].hint.as_deref(), Some("Set up the registry"));
+}
+
+#[test]
+fn test_build_info_check() {
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+    let outcome = &run_checks([crate::checks::build_info_check!()].iter())[0];
+    assert_eq!(outcome.name, "build");
+    assert_eq!(outcome.result, CheckResult::Ok);
+    assert!(
+        outcome
+            .message
+            .starts_with(&format!("{profile} build for ")),
+        "{outcome:?}"
+    );
+    assert_eq!(outcome.details[0], ("profile".into(), profile.into()));
+    assert_eq!(outcome.details[1].0, "target");
+
+    let check = crate::checks::build_info_check!(features = ["fonts", "serde"]);
+    let outcome = &run_checks([check].iter())[0];
+    let features = match (cfg!(feature = "fonts"), cfg!(feature = "serde")) {
+        (true, true) => "fonts, serde",
+        (true, false) => "fonts",
+        (false, true) => "serde",
+        (false, false) => "none",
+    };
+    assert_eq!(outcome.details[2], ("features".into(), features.into()));
+}
+
+#[test]
+fn test_diff_reports() {
+    let old = [
+        Check::new("build", || {
+            Ok((CheckResult::Ok, "Official release".to_string()))
+        }),
+        Check::new("fonts", || Ok((CheckResult::Ok, "Font found".to_string()))),
+        Check::new("host", || Ok((CheckResult::Info, "linux".to_string()))),
+    ];
+    let new = [
+        Check::new("build", || {
+            Ok((
+                CheckResult::Warning,
+                "Github CI build\nNot official".to_string(),
+            ))
+        }),
+        Check::new("host", || Ok((CheckResult::Info, "linux".to_string()))),
+        Check::new("proxy", || {
+            Ok((CheckResult::Warning, "HTTPS_PROXY is set".to_string()))
+        }),
+    ];
+    let old = SavedReport::new(&run_checks(old.iter()));
+    let new = SavedReport::new(&run_checks(new.iter()));
+
+    let changes = diff_reports(&old, &new);
+    assert_eq!(changes.len(), 3);
+    assert!(matches!(&changes[0], ReportChange::Changed { new, .. } if new.name == "build"));
+    assert!(matches!(&changes[1], ReportChange::Added(check) if check.name == "proxy"));
+    assert!(matches!(&changes[2], ReportChange::Removed(check) if check.name == "fonts"));
+
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    render_diff(&mut out_buf, &changes).unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        Changed  build  Ok -> Warning
+                        was: Official release
+                        now: Github CI build
+                             Not official
+        Added    proxy  Warning  HTTPS_PROXY is set
+        Removed  fonts  Ok       Font found\n"};
+    assert_eq!(out, expected);
+
+    let mut out_buf = Vec::new();
+    render_diff(&mut out_buf, &diff_reports(&new, &new)).unwrap();
+    assert_eq!(String::from_utf8(out_buf).unwrap(), "No changes\n");
+    // Names are aligned by display width
+    let wide = [
+        Check::new("字体", || Ok((CheckResult::Ok, "Font found".to_string()))),
+        Check::new("proxy", || Ok((CheckResult::Ok, "Not set".to_string()))),
+    ];
+    let wide = SavedReport::new(&run_checks(wide.iter()));
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    render_diff(&mut out_buf, &diff_reports(&SavedReport::default(), &wide)).unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        Added    字体   Ok       Font found
+        Added    proxy  Ok       Not set\n"};
+    assert_eq!(out, expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_saved_report_from_json() {
+    let checks = [
+        Check::new("build", || {
+            Ok((CheckResult::Warning, "CI build".to_string()))
+        })
+        .with_hint("Use an official release"),
+        Check::new("host", || Ok((CheckResult::Info, "linux".to_string()))),
+    ];
+    let outcomes = run_checks(checks.iter());
+    let mut out_buf = Vec::new();
+    crate::render_json(&mut out_buf, &outcomes).unwrap();
+
+    let loaded = SavedReport::from_json(out_buf.as_slice()).unwrap();
+    assert_eq!(loaded, SavedReport::new(&outcomes));
+    assert!(diff_reports(&loaded, &SavedReport::new(&outcomes)).is_empty());
+
+    let err = SavedReport::from_json("{\"checks\": 5}".as_bytes()).unwrap_err();
+    assert!(matches!(err, MedicError::InvalidReport(_)), "{err:?}");
+    assert_eq!(err.to_string(), "Invalid saved report");
+}
+
+#[test]
+fn test_verbose_extended() {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let checks = [Check::new_detailed("config", {
+        let calls = Arc::clone(&calls);
+        move || {
+            // The extended output can use what the check found
+            let unknown = ["foo"];
+            let calls = Arc::clone(&calls);
+            Ok(
+                DetailedResult::new(CheckResult::Warning, "Config has unknown keys").extended(
+                    move || {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        Ok(format!("[main]\n{} = 1", unknown[0]))
+                    },
+                ),
+            )
+        }
+    })];
+
+    // Compact by default, without calling the function
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    medic_with_options(&mut out_buf, checks.iter(), &MedicOptions::new()).unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK   MESSAGE
+        Warning  config  Config has unknown keys\n"};
+    assert_eq!(out, expected);
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    medic_with_options(
+        &mut out_buf,
+        checks.iter(),
+        &MedicOptions::new().verbose(true),
+    )
+    .unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT   CHECK   MESSAGE
+        Warning  config  Config has unknown keys
+                         [main]
+                         foo = 1\n"};
+    assert_eq!(out, expected);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let failing = [Check::new_detailed("Plain", || {
+        Ok(DetailedResult::new(CheckResult::Ok, "Fine").extended(|| Err("No access".into())))
+    })];
+    let outcomes = run_checks_with(failing.iter(), &MedicOptions::new().verbose(true));
+    assert_eq!(outcomes[0].result, CheckResult::Ok);
+    assert_eq!(
+        outcomes[0].extended.as_deref(),
+        Some("Failed to get extended output: No access")
+    );
+
+    // Collecting the extended output counts towards the timeout
+    let slow = [Check::new_detailed("Slow", || {
+        Ok(DetailedResult::new(CheckResult::Ok, "Fine").extended(|| {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            Ok("Too late".to_string())
+        }))
+    })];
+    let options = MedicOptions::new().check_timeout(std::time::Duration::from_millis(50));
+    let outcomes = run_checks_with(slow.iter(), &options);
+    assert_eq!(outcomes[0].result, CheckResult::Ok);
+    assert_eq!(outcomes[0].extended, None);
+    let outcomes = run_checks_with(slow.iter(), &options.verbose(true));
+    assert_eq!(outcomes[0].result, CheckResult::Fatal);
+    assert!(outcomes[0].errored);
+    assert_eq!(outcomes[0].extended, None);
+}
+
+#[test]
+fn test_hostile_terminal_rendering() {
+    let checks = [
+        Check::new("字体", || {
+            Ok((CheckResult::Ok, "Noto Sans CJK 日本語 found".to_string()))
+        }),
+        Check::new("output", || {
+            Ok((
+                CheckResult::Warning,
+                "a\tb\r\nred \x1b[31mtext\x1b[0m".to_string(),
+            ))
+        }),
+    ];
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    medic_with_options(&mut out_buf, checks.iter(), &MedicOptions::new()).unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {r"
+        RESULT   CHECK   MESSAGE
+        Ok       字体    Noto Sans CJK 日本語 found
+        Warning  output  a       b
+                         red \u{1b}[31mtext\u{1b}[0m
+    "};
+    assert_eq!(out, expected);
+
+    // Wrapping by display width, not characters
+    let mut out_buf = anstream::StripStream::new(Vec::new());
+    medic_with_options(
+        &mut out_buf,
+        checks[..1].iter(),
+        &MedicOptions::new().width(Some(30)),
+    )
+    .unwrap();
+    let out = String::from_utf8(out_buf.into_inner()).unwrap();
+    let expected = indoc::indoc! {"
+        RESULT  CHECK  MESSAGE
+        Ok      字体   Noto Sans CJK
+                       日本語 found\n"};
+    assert_eq!(out, expected);
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_medic_log() {
+    use std::sync::Mutex;
+
+    static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+    struct TestLogger;
+
+    impl log::Log for TestLogger {
+        fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+            metadata.target() == "medic"
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            if self.enabled(record.metadata()) {
+                let mut records = RECORDS.lock().unwrap();
+                records.push((record.level(), record.args().to_string()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    log::set_logger(&TestLogger).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let checks = [
+        Check::new("version", || Ok((CheckResult::Ok, "1.2.3".to_string()))),
+        Check::new("config", || {
+            Ok((CheckResult::Warning, "Config missing".to_string()))
+        })
+        .with_hint("Run `mytool init`"),
+        Check::new("broken", || Err("Very bad".into())),
+    ];
+    let result = crate::medic_log(checks.iter(), &MedicOptions::new());
+    assert_eq!(result, CheckResult::Fatal);
+    assert_eq!(
+        *RECORDS.lock().unwrap(),
+        [
+            (log::Level::Info, "version: Ok: 1.2.3".to_string()),
+            (
+                log::Level::Warn,
+                "config: Warning: Config missing (hint: Run `mytool init`)".to_string()
+            ),
+            (log::Level::Error, "broken: Fatal: Very bad".to_string()),
+        ]
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_medic_trace() {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    /// Fields of an event, in order
+    type Fields = Vec<(String, String)>;
+
+    /// Subscriber collecting the level and fields of all events
+    struct TestSubscriber(Arc<Mutex<Vec<(tracing::Level, Fields)>>>);
+
+    /// Visitor collecting the fields of an event
+    struct FieldVisitor(Fields);
+
+    impl tracing::field::Visit for FieldVisitor {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.push((field.name().to_string(), value.to_string()));
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+
+    impl tracing::Subscriber for TestSubscriber {
+        fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+            metadata.target() == "medic"
+        }
+
+        fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut visitor = FieldVisitor(vec![]);
+            event.record(&mut visitor);
+            let mut events = self.0.lock().unwrap();
+            events.push((*event.metadata().level(), visitor.0));
+        }
+
+        fn enter(&self, _span: &tracing::span::Id) {}
+
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    let checks = [
+        Check::new("version", || Ok((CheckResult::Ok, "1.2.3".to_string()))),
+        Check::new("config", || {
+            Ok((CheckResult::Warning, "Config missing".to_string()))
+        })
+        .with_hint("Run `mytool init`"),
+        Check::new("broken", || Err("Very bad".into())),
+    ];
+    let events = Arc::new(Mutex::new(vec![]));
+    let subscriber = TestSubscriber(Arc::clone(&events));
+    let result = tracing::subscriber::with_default(subscriber, || {
+        crate::medic_trace(
+            checks.iter(),
+            &MedicOptions::new().min_level(CheckResult::Warning),
+        )
+    });
+    assert_eq!(result, CheckResult::Fatal);
+
+    let field = |name: &str, value: &str| (name.to_string(), value.to_string());
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            (
+                tracing::Level::WARN,
+                vec![
+                    field("message", "Config missing"),
+                    field("check", "config"),
+                    field("result", "Warning"),
+                    field("hint", "Run `mytool init`"),
+                ]
+            ),
+            (
+                tracing::Level::ERROR,
+                vec![
+                    field("message", "Very bad"),
+                    field("check", "broken"),
+                    field("result", "Fatal"),
+                ]
+            ),
+        ]
+    );
+}